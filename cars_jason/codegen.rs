@@ -0,0 +1,1190 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde_json::{Map, Value};
+
+// Derives every type gets when the schema doesn't list its own
+const DEFAULT_DERIVES: [&str; 1] = ["Debug"];
+
+// Types a field can name without the schema defining them
+const PRIMITIVE_TYPES: [&str; 17] = [
+    "bool", "char", "String", "f32", "f64",
+    "i8", "i16", "i32", "i64", "i128", "isize",
+    "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+// Strict and reserved words, none of them can name a type, field or variant
+const KEYWORDS: [&str; 52] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+    "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract",
+    "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof", "unsized",
+    "virtual", "yield",
+];
+
+// Names the generated code uses for itself, a type with one of these names would replace them
+const RESERVED_TYPES: [&str; 5] = ["Option", "Vec", "Box", "std", "serde"];
+
+// Where a serde attribute goes, serde takes a different set on each
+#[derive(Clone, Copy, PartialEq)]
+enum AttributeTarget {
+    Struct,
+    Enum,
+    Field,
+}
+
+impl AttributeTarget {
+    fn described(self) -> &'static str {
+        match self {
+            AttributeTarget::Struct => "a struct",
+            AttributeTarget::Enum => "an enum",
+            AttributeTarget::Field => "a field",
+        }
+    }
+}
+
+// What an attribute takes: a bare flag, a string or either
+#[derive(Clone, Copy, PartialEq)]
+enum AttributeValue {
+    Flag,
+    Text,
+    Either,
+}
+
+// The serde attributes a schema can set and where serde accepts them.
+// Enum tagging has its own keys, so tag, content and untagged aren't here.
+const SERDE_ATTRIBUTES: [(&str, AttributeValue, &[AttributeTarget]); 26] = {
+    use AttributeTarget::*;
+    use AttributeValue::*;
+    [
+        ("rename", Text, &[Struct, Enum, Field]),
+        ("rename_all", Text, &[Struct, Enum]),
+        ("rename_all_fields", Text, &[Enum]),
+        ("deny_unknown_fields", Flag, &[Struct, Enum]),
+        ("bound", Text, &[Struct, Enum, Field]),
+        ("default", Either, &[Struct, Field]),
+        ("remote", Text, &[Struct, Enum]),
+        ("transparent", Flag, &[Struct]),
+        ("from", Text, &[Struct, Enum]),
+        ("try_from", Text, &[Struct, Enum]),
+        ("into", Text, &[Struct, Enum]),
+        ("crate", Text, &[Struct, Enum]),
+        ("expecting", Text, &[Struct, Enum]),
+        ("variant_identifier", Flag, &[Enum]),
+        ("field_identifier", Flag, &[Enum]),
+        ("alias", Text, &[Field]),
+        ("flatten", Flag, &[Field]),
+        ("skip", Flag, &[Field]),
+        ("skip_serializing", Flag, &[Field]),
+        ("skip_deserializing", Flag, &[Field]),
+        ("skip_serializing_if", Text, &[Field]),
+        ("serialize_with", Text, &[Field]),
+        ("deserialize_with", Text, &[Field]),
+        ("with", Text, &[Field]),
+        ("borrow", Either, &[Field]),
+        ("getter", Text, &[Field]),
+    ]
+};
+
+// What rename_all and rename_all_fields can be, as serde spells them
+const SERDE_CASES: [&str; 8] = [
+    "lowercase", "UPPERCASE", "PascalCase", "camelCase",
+    "snake_case", "SCREAMING_SNAKE_CASE", "kebab-case", "SCREAMING-KEBAB-CASE",
+];
+
+const STRUCT_KEYS: [&str; 4] = ["name", "derive", "serde", "fields"];
+const ENUM_KEYS: [&str; 8] = ["name", "derive", "serde", "enum", "tagging", "tag", "content", "default"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    // As cargo spells it in `cargo::error=` and `cargo::warning=`
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// One problem in the schema, at the line and column where the offending JSON starts
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub context: String, // The type, `Type.field` or `Type::Variant`, empty for the top level
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.context.is_empty() {
+            write!(f, "{}: ", self.context)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+pub struct Generated {
+    pub code: String,
+    pub warnings: Vec<Diagnostic>,
+}
+
+// A field's type as the schema spells it: a name, or a container of other types
+enum TypeExpr {
+    Named { name: String, path: String },
+    Vec(Box<TypeExpr>),
+    Option(Box<TypeExpr>),
+    Map(Box<TypeExpr>, Box<TypeExpr>),
+}
+
+// Tuple variants use these too, named by position
+struct FieldDef {
+    name: String,
+    context: String,
+    ty: TypeExpr,
+    serde: Option<String>,
+}
+
+// `"Petrol": null`, `"Hybrid": ["f64", "f64"]` or `"Electric": { "kwh": "f64" }`
+enum VariantShape {
+    Unit,
+    Tuple(Vec<FieldDef>),
+    Struct(Vec<FieldDef>),
+}
+
+struct VariantDef {
+    name: String,
+    shape: VariantShape,
+}
+
+// A struct from "fields", or an enum from "enum"
+enum TypeKind {
+    Struct(Vec<FieldDef>),
+    Enum { variants: Vec<VariantDef>, default: Option<String>, internally_tagged: bool },
+}
+
+struct TypeDef {
+    name: String,
+    derives: Vec<String>,
+    serde: Vec<String>, // Attribute lines, the type's own then the enum tagging
+    kind: TypeKind,
+}
+
+impl TypeDef {
+    fn fields(&self) -> Vec<&FieldDef> {
+        match &self.kind {
+            TypeKind::Struct(fields) => fields.iter().collect(),
+            TypeKind::Enum { variants, .. } => variants.iter()
+                .flat_map(|variant| match &variant.shape {
+                    VariantShape::Unit => Vec::new(),
+                    VariantShape::Tuple(fields) | VariantShape::Struct(fields) => fields.iter().collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Checks the whole schema and only generates code when nothing is wrong with it.
+// Every problem found is reported, not just the first.
+pub fn generate(source: &str) -> Result<Generated, Vec<Diagnostic>> {
+    let parsed: Value = serde_json::from_str(source).map_err(|err| {
+        // serde_json puts the position on the end of its message, it's in the diagnostic already
+        let message = err.to_string();
+        let message = message.split(" at line ").next().unwrap_or_default();
+        vec![Diagnostic {
+            severity: Severity::Error,
+            line: err.line(),
+            column: err.column(),
+            context: String::new(),
+            message: format!("Not valid JSON: {}", message),
+        }]
+    })?;
+
+    let mut checker = Checker { spans: Spans::index(source), diagnostics: Vec::new() };
+    let types = checker.schema(&parsed);
+    checker.check_references(&types);
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        return Err(diagnostics);
+    }
+    Ok(Generated { code: emit(&types), warnings: diagnostics })
+}
+
+fn emit(types: &[TypeDef]) -> String {
+    let boxed = boxed_references(types);
+
+    let mut output = String::new();
+    for type_def in types {
+        let is_boxed = |target: &str| boxed.contains(&(type_def.name.clone(), target.to_string()));
+        output.push_str(&format!("#[derive({})]\n", derive_paths(&type_def.derives).join(", ")));
+        for attribute in &type_def.serde {
+            output.push_str(&format!("{}\n", attribute));
+        }
+
+        match &type_def.kind {
+            TypeKind::Struct(fields) => {
+                output.push_str(&format!("pub struct {} {{\n", type_def.name));
+                push_fields(&mut output, fields, "    ", "pub ", &is_boxed);
+                output.push_str("}\n\n");
+            }
+            TypeKind::Enum { variants, default, .. } => {
+                output.push_str(&format!("pub enum {} {{\n", type_def.name));
+                for variant in variants {
+                    if default.as_ref() == Some(&variant.name) {
+                        output.push_str("    #[default]\n");
+                    }
+                    match &variant.shape {
+                        VariantShape::Unit => output.push_str(&format!("    {},\n", variant.name)),
+                        VariantShape::Tuple(fields) => {
+                            let types: Vec<String> = fields.iter().map(|f| rust_type(&f.ty, &is_boxed)).collect();
+                            output.push_str(&format!("    {}({}),\n", variant.name, types.join(", ")));
+                        }
+                        VariantShape::Struct(fields) => {
+                            output.push_str(&format!("    {} {{\n", variant.name));
+                            push_fields(&mut output, fields, "        ", "", &is_boxed);
+                            output.push_str("    },\n");
+                        }
+                    }
+                }
+                output.push_str("}\n\n");
+            }
+        }
+    }
+    output
+}
+
+// Struct fields are pub, fields of an enum variant can't be
+fn push_fields(output: &mut String, fields: &[FieldDef], indent: &str, visibility: &str, is_boxed: &dyn Fn(&str) -> bool) {
+    for field in fields {
+        if let Some(attribute) = &field.serde {
+            output.push_str(&format!("{}{}\n", indent, attribute));
+        }
+        output.push_str(&format!("{}{}{}: {},\n", indent, visibility, field.name, rust_type(&field.ty, is_boxed)));
+    }
+}
+
+// Walks the parsed schema, building the types and noting every problem against its JSON path
+struct Checker {
+    spans: Spans,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn report(&mut self, severity: Severity, path: &str, context: &str, message: String) {
+        let (line, column) = self.spans.find(path);
+        self.diagnostics.push(Diagnostic { severity, line, column, context: context.to_string(), message });
+    }
+
+    fn error(&mut self, path: &str, context: &str, message: String) {
+        self.report(Severity::Error, path, context, message);
+    }
+
+    fn warning(&mut self, path: &str, context: &str, message: String) {
+        self.report(Severity::Warning, path, context, message);
+    }
+
+    // serde_json keeps only the last of a repeated key, so the spans are the only place they show up
+    fn check_duplicates(&mut self, path: &str, context: &str, what: &str) {
+        for (key, (line, column)) in self.spans.duplicates(path) {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                line,
+                column,
+                context: context.to_string(),
+                message: format!("{} '{}' is listed more than once", what, key),
+            });
+        }
+    }
+
+    fn check_keys(&mut self, object: &Map<String, Value>, path: &str, context: &str, known: &[&str]) {
+        for key in object.keys().filter(|key| !known.contains(&key.as_str())) {
+            self.warning(&format!("{}/{}", path, key), context, format!("Unknown key '{}' is ignored", key));
+        }
+        self.check_duplicates(path, context, "Key");
+    }
+
+    // Types and variants are UpperCamelCase, fields snake_case. Anything else only gets a warning,
+    // it still compiles.
+    fn check_name(&mut self, path: &str, context: &str, name: &str, what: &str) {
+        if !is_identifier(name) {
+            self.error(path, context, format!("'{}' is not a valid Rust identifier for a {}", name, what));
+        } else if KEYWORDS.contains(&name) {
+            self.error(path, context, format!("'{}' is a Rust keyword, name the {} something else and use a serde rename", name, what));
+        } else if what == "field" && name.chars().any(|c| c.is_ascii_uppercase()) {
+            self.warning(path, context, format!("Field '{}' should be snake_case", name));
+        } else if what != "field" && (name.contains('_') || !name.starts_with(|c: char| c.is_ascii_uppercase())) {
+            self.warning(path, context, format!("{} '{}' should be UpperCamelCase", capitalised(what), name));
+        }
+    }
+
+    fn schema(&mut self, parsed: &Value) -> Vec<TypeDef> {
+        let Some(root) = parsed.as_object() else {
+            self.error("", "", String::from("Expected an object with a 'types' list"));
+            return Vec::new();
+        };
+        self.check_keys(root, "", "", &["derive", "types"]);
+
+        // Listed once at the top, each type can add its own on top of these
+        let global_derives = match root.get("derive") {
+            Some(derives) => self.derive_list(derives, "/derive", ""),
+            None => DEFAULT_DERIVES.iter().map(|d| d.to_string()).collect(),
+        };
+        let Some(types_array) = root.get("types").and_then(Value::as_array) else {
+            self.error("/types", "", String::from("Expected 'types' to be a list of type definitions"));
+            return Vec::new();
+        };
+
+        let mut types: Vec<TypeDef> = Vec::new();
+        let mut defined_on: HashMap<String, usize> = HashMap::new();
+        for (i, type_value) in types_array.iter().enumerate() {
+            let path = format!("/types/{}", i);
+            let Some(type_def) = self.type_def(type_value, &path, &global_derives) else { continue; };
+
+            let name_path = format!("{}/name", path);
+            let (line, _) = self.spans.find(&name_path);
+            if let Some(first_line) = defined_on.get(&type_def.name) {
+                self.error(&name_path, &type_def.name, format!("Type '{}' is already defined on line {}", type_def.name, first_line));
+                continue;
+            }
+            defined_on.insert(type_def.name.clone(), line);
+            types.push(type_def);
+        }
+        types
+    }
+
+    fn type_def(&mut self, value: &Value, path: &str, global_derives: &[String]) -> Option<TypeDef> {
+        let Some(object) = value.as_object() else {
+            self.error(path, "", String::from("Expected a type definition object"));
+            return None;
+        };
+        let Some(name) = object.get("name").and_then(Value::as_str) else {
+            self.error(&format!("{}/name", path), "", String::from("Expected 'name' to be a string"));
+            return None;
+        };
+        let name_path = format!("{}/name", path);
+        self.check_name(&name_path, name, name, "type");
+        if PRIMITIVE_TYPES.contains(&name) {
+            self.error(&name_path, name, format!("'{}' would hide the built in type of the same name", name));
+        } else if RESERVED_TYPES.contains(&name) {
+            self.error(&name_path, name, format!("'{}' would hide the {} the generated code uses", name, name));
+        }
+
+        let mut derives = global_derives.to_vec();
+        if let Some(extra) = object.get("derive") {
+            for derive in self.derive_list(extra, &format!("{}/derive", path), name) {
+                if !derives.contains(&derive) {
+                    derives.push(derive);
+                }
+            }
+        }
+        let uses_serde = derives.iter().any(|d| d == "Serialize" || d == "Deserialize");
+        let target = if object.contains_key("enum") { AttributeTarget::Enum } else { AttributeTarget::Struct };
+        let mut serde: Vec<String> = self.serde_attribute(object, path, name, target, uses_serde).into_iter().collect();
+        // A bare `default` fills missing fields from Default::default(), so the struct has to have one
+        let serde_default = object.get("serde").and_then(|serde| serde.get("default"));
+        if serde_default == Some(&Value::Bool(true)) && !derives.iter().any(|d| d == "Default") {
+            self.error(&format!("{}/serde/default", path), name, String::from("Has serde 'default' but doesn't derive Default"));
+        }
+
+        let kind = match (object.get("fields"), object.get("enum")) {
+            (Some(fields), None) => {
+                self.check_keys(object, path, name, &STRUCT_KEYS);
+                TypeKind::Struct(self.fields(fields, &format!("{}/fields", path), name, uses_serde))
+            }
+            (None, Some(variants)) => {
+                self.check_keys(object, path, name, &ENUM_KEYS);
+                let variants = self.variants(variants, &format!("{}/enum", path), name, uses_serde);
+                serde.extend(self.tagging_attribute(object, path, name, &variants, uses_serde));
+                let default = self.default_variant(object, path, name, &variants, &derives);
+                let internally_tagged = object.get("tagging").and_then(Value::as_str) == Some("internal");
+                TypeKind::Enum { variants, default, internally_tagged }
+            }
+            (Some(_), Some(_)) => {
+                self.error(path, name, String::from("Has both 'fields' and 'enum', a type is either a struct or an enum"));
+                return None;
+            }
+            (None, None) => {
+                self.error(path, name, String::from("Needs 'fields' for a struct or 'enum' for an enum"));
+                return None;
+            }
+        };
+
+        Some(TypeDef { name: name.to_string(), derives, serde, kind })
+    }
+
+    fn derive_list(&mut self, value: &Value, path: &str, context: &str) -> Vec<String> {
+        let Some(items) = value.as_array() else {
+            self.error(path, context, String::from("Expected 'derive' to be a list of trait names"));
+            return Vec::new();
+        };
+        let mut derives: Vec<String> = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            let item_path = format!("{}/{}", path, i);
+            match item.as_str() {
+                Some(derive) if !derive.split("::").all(is_identifier) => {
+                    self.error(&item_path, context, format!("'{}' is not a trait name", derive));
+                }
+                Some(derive) if derives.iter().any(|d| d == derive) => {
+                    self.warning(&item_path, context, format!("'{}' is listed more than once", derive));
+                }
+                Some(derive) => derives.push(derive.to_string()),
+                None => self.error(&item_path, context, String::from("Expected a trait name")),
+            }
+        }
+        derives
+    }
+
+    // `"serde": { "rename": "x", "default": true }` becomes `#[serde(rename = "x", default)]`.
+    // Strings are quoted, true is a bare flag and false leaves the attribute out.
+    fn serde_attribute(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+        context: &str,
+        target: AttributeTarget,
+        uses_serde: bool,
+    ) -> Option<String> {
+        let serde = object.get("serde")?;
+        let serde_path = format!("{}/serde", path);
+        let Some(serde) = serde.as_object() else {
+            self.error(&serde_path, context, String::from("Expected 'serde' to be an object of attributes"));
+            return None;
+        };
+        if !uses_serde {
+            self.error(&serde_path, context, String::from("Has serde attributes but derives neither Serialize nor Deserialize"));
+        }
+        self.check_duplicates(&serde_path, context, "Serde attribute");
+
+        let mut parts = Vec::new();
+        for (key, value) in serde {
+            let key_path = format!("{}/{}", serde_path, key);
+            let known = SERDE_ATTRIBUTES.iter().find(|(name, _, _)| name == key);
+            let Some((_, takes, _)) = known.filter(|(_, _, targets)| targets.contains(&target)) else {
+                let message = match key.as_str() {
+                    "tag" | "content" | "untagged" if target == AttributeTarget::Enum => {
+                        String::from("Set the enum tagging with the 'tagging', 'tag' and 'content' keys instead")
+                    }
+                    _ => format!("Serde has no '{}' attribute for {}", key, target.described()),
+                };
+                self.error(&key_path, context, message);
+                continue;
+            };
+            match (value, takes) {
+                (Value::Bool(false), _) => {}
+                (Value::Bool(true), AttributeValue::Flag | AttributeValue::Either) => parts.push(key.clone()),
+                (Value::String(text), _) if key.starts_with("rename_all") && !SERDE_CASES.contains(&text.as_str()) => {
+                    self.error(&key_path, context, format!("Serde has no '{}' case, expected one of {}", text, SERDE_CASES.join(", ")));
+                }
+                (Value::String(text), AttributeValue::Text | AttributeValue::Either) => parts.push(format!("{} = {:?}", key, text)),
+                (Value::Bool(true), _) => self.error(&key_path, context, format!("Serde '{}' needs a string", key)),
+                (Value::String(_), _) => self.error(&key_path, context, format!("Serde '{}' is a flag, set it to true", key)),
+                _ => self.error(&key_path, context, format!("Expected serde '{}' to be a string or a bool", key)),
+            }
+        }
+        (!parts.is_empty()).then(|| format!("#[serde({})]", parts.join(", ")))
+    }
+
+    fn fields(&mut self, value: &Value, path: &str, owner: &str, uses_serde: bool) -> Vec<FieldDef> {
+        let Some(fields) = value.as_object() else {
+            self.error(path, owner, String::from("Expected 'fields' to be an object of field names and types"));
+            return Vec::new();
+        };
+        self.check_duplicates(path, owner, "Field");
+
+        let mut parsed = Vec::new();
+        for (field_name, field_value) in fields {
+            let field_path = format!("{}/{}", path, field_name);
+            let context = format!("{}.{}", owner, field_name);
+            self.check_name(&field_path, &context, field_name, "field");
+
+            // An object with a 'type' is the type plus its serde attributes, anything else is just the type
+            let (ty, serde) = match field_value.as_object().filter(|field| field.contains_key("type")) {
+                Some(field) => {
+                    self.check_keys(field, &field_path, &context, &["type", "serde"]);
+                    let ty = self.type_expr(&field["type"], &format!("{}/type", field_path), &context);
+                    (ty, self.serde_attribute(field, &field_path, &context, AttributeTarget::Field, uses_serde))
+                }
+                None => (self.type_expr(field_value, &field_path, &context), None),
+            };
+            if let Some(ty) = ty {
+                parsed.push(FieldDef { name: field_name.clone(), context, ty, serde });
+            }
+        }
+        parsed
+    }
+
+    fn variants(&mut self, value: &Value, path: &str, owner: &str, uses_serde: bool) -> Vec<VariantDef> {
+        let Some(variants) = value.as_object() else {
+            self.error(path, owner, String::from("Expected 'enum' to be an object of variant names and shapes"));
+            return Vec::new();
+        };
+        self.check_duplicates(path, owner, "Variant");
+
+        let mut parsed = Vec::new();
+        for (variant_name, shape) in variants {
+            let variant_path = format!("{}/{}", path, variant_name);
+            let context = format!("{}::{}", owner, variant_name);
+            self.check_name(&variant_path, &context, variant_name, "variant");
+
+            let shape = match shape {
+                Value::Null => VariantShape::Unit,
+                Value::Array(types) => VariantShape::Tuple(
+                    types.iter()
+                        .enumerate()
+                        .filter_map(|(i, ty)| {
+                            let ty = self.type_expr(ty, &format!("{}/{}", variant_path, i), &context)?;
+                            Some(FieldDef { name: i.to_string(), context: context.clone(), ty, serde: None })
+                        })
+                        .collect(),
+                ),
+                Value::Object(_) => VariantShape::Struct(self.fields(shape, &variant_path, &context, uses_serde)),
+                _ => {
+                    self.error(&variant_path, &context, String::from("Expected null, a list of types or an object of fields"));
+                    continue;
+                }
+            };
+            parsed.push(VariantDef { name: variant_name.clone(), shape });
+        }
+        parsed
+    }
+
+    // "Wheel", { "vec": "Wheel" }, { "option": "f64" } or { "map": { "key": "String", "value": "Wheel" } }
+    fn type_expr(&mut self, value: &Value, path: &str, context: &str) -> Option<TypeExpr> {
+        if let Some(name) = value.as_str() {
+            return Some(TypeExpr::Named { name: name.to_string(), path: path.to_string() });
+        }
+        let Some((kind, inner)) = value.as_object().filter(|container| container.len() == 1).and_then(|c| c.iter().next()) else {
+            self.error(path, context, String::from("Expected a type name, or an object with one of 'vec', 'option' or 'map'"));
+            return None;
+        };
+        let inner_path = format!("{}/{}", path, kind);
+        match kind.as_str() {
+            "vec" => Some(TypeExpr::Vec(Box::new(self.type_expr(inner, &inner_path, context)?))),
+            "option" => Some(TypeExpr::Option(Box::new(self.type_expr(inner, &inner_path, context)?))),
+            "map" => {
+                let Some(map) = inner.as_object() else {
+                    self.error(&inner_path, context, String::from("Expected 'map' to be an object with a 'key' and a 'value' type"));
+                    return None;
+                };
+                self.check_keys(map, &inner_path, context, &["key", "value"]);
+                let (Some(key), Some(value)) = (map.get("key"), map.get("value")) else {
+                    self.error(&inner_path, context, String::from("Expected 'map' to have both a 'key' and a 'value' type"));
+                    return None;
+                };
+                let key = self.type_expr(key, &format!("{}/key", inner_path), context);
+                let value = self.type_expr(value, &format!("{}/value", inner_path), context);
+                Some(TypeExpr::Map(Box::new(key?), Box::new(value?)))
+            }
+            _ => {
+                self.error(path, context, format!("Unknown container '{}', expected 'vec', 'option' or 'map'", kind));
+                None
+            }
+        }
+    }
+
+    // "tagging" picks how serde writes the variant: "external" (serde's own default, `{ "Electric": { ... } }`),
+    // "internal" (`{ "type": "Electric", ... }`), "adjacent" (`{ "type": "Electric", "content": { ... } }`)
+    // or "untagged" (just the contents). "tag" and "content" rename the keys.
+    fn tagging_attribute(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+        name: &str,
+        variants: &[VariantDef],
+        uses_serde: bool,
+    ) -> Option<String> {
+        let tagging_path = format!("{}/tagging", path);
+        let tagging = match object.get("tagging") {
+            None => "external",
+            Some(Value::String(tagging)) => tagging.as_str(),
+            Some(_) => {
+                self.error(&tagging_path, name, String::from("Expected 'tagging' to be a string"));
+                return None;
+            }
+        };
+        let key = |checker: &mut Self, key: &str, fallback: &str| match object.get(key) {
+            None => fallback.to_string(),
+            Some(Value::String(text)) => text.clone(),
+            Some(_) => {
+                checker.error(&format!("{}/{}", path, key), name, format!("Expected '{}' to be a string", key));
+                fallback.to_string()
+            }
+        };
+        let unused = |checker: &mut Self, key: &str, used_by: &str| {
+            if object.contains_key(key) {
+                checker.warning(&format!("{}/{}", path, key), name, format!("'{}' is only used with {} tagging", key, used_by));
+            }
+        };
+        if tagging != "external" && !uses_serde {
+            self.error(&tagging_path, name, format!("Is {} tagged but derives neither Serialize nor Deserialize", tagging));
+        }
+
+        match tagging {
+            "external" | "untagged" => {
+                unused(self, "tag", "internal or adjacent");
+                unused(self, "content", "adjacent");
+                (tagging == "untagged").then(|| String::from("#[serde(untagged)]"))
+            }
+            "internal" => {
+                unused(self, "content", "adjacent");
+                // The tag goes in among the variant's own fields, so there have to be named fields to put it in
+                for variant in variants {
+                    if matches!(&variant.shape, VariantShape::Tuple(fields) if fields.len() != 1) {
+                        self.error(
+                            &format!("{}/enum/{}", path, variant.name),
+                            &format!("{}::{}", name, variant.name),
+                            String::from("Tuple variants can't be internally tagged, use named fields or adjacent tagging"),
+                        );
+                    }
+                }
+                Some(format!("#[serde(tag = {:?})]", key(self, "tag", "type")))
+            }
+            "adjacent" => {
+                let tag = key(self, "tag", "type");
+                let content = key(self, "content", "content");
+                if tag == content {
+                    self.error(&format!("{}/content", path), name, format!("The tag and the content can't both be called '{}'", tag));
+                }
+                Some(format!("#[serde(tag = {:?}, content = {:?})]", tag, content))
+            }
+            _ => {
+                self.error(&tagging_path, name, format!("Unknown tagging '{}', expected external, internal, adjacent or untagged", tagging));
+                None
+            }
+        }
+    }
+
+    // Deriving Default on an enum needs one unit variant marked as the default
+    fn default_variant(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+        name: &str,
+        variants: &[VariantDef],
+        derives: &[String],
+    ) -> Option<String> {
+        let default_path = format!("{}/default", path);
+        let derives_default = derives.iter().any(|d| d == "Default");
+        let default = match (object.get("default"), derives_default) {
+            (None, false) => return None,
+            (Some(_), false) => {
+                self.warning(&default_path, name, String::from("'default' is ignored, the enum doesn't derive Default"));
+                return None;
+            }
+            (None, true) => {
+                self.error(path, name, String::from("Derives Default, so 'default' has to name one of its unit variants"));
+                return None;
+            }
+            (Some(default), true) => default,
+        };
+
+        match default.as_str().map(|default| (default, variants.iter().find(|v| v.name == default))) {
+            Some((default, Some(VariantDef { shape: VariantShape::Unit, .. }))) => Some(default.to_string()),
+            Some((default, Some(_))) => {
+                self.error(&default_path, name, format!("Default variant '{}' has to be a unit variant", default));
+                None
+            }
+            Some((default, None)) => {
+                self.error(&default_path, name, format!("Default variant '{}' is not one of the variants", default));
+                None
+            }
+            None => {
+                self.error(&default_path, name, String::from("Expected 'default' to be a variant name"));
+                None
+            }
+        }
+    }
+
+    // Every name is a primitive or another type in the schema, and map keys are ones serde can write as JSON keys
+    fn check_references(&mut self, types: &[TypeDef]) {
+        let defined: HashSet<&str> = types.iter().map(|t| t.name.as_str()).collect();
+        for type_def in types {
+            for field in type_def.fields() {
+                self.check_type(&field.ty, &defined, &field.context);
+            }
+            if let TypeKind::Enum { variants, internally_tagged: true, .. } = &type_def.kind {
+                self.check_internal_newtypes(variants, types);
+            }
+        }
+    }
+
+    // Serde writes an internally tagged newtype by putting the tag in among the inner value's
+    // fields, so the inner value has to have fields: a struct or a map. Anything else only
+    // fails when it's serialized.
+    fn check_internal_newtypes(&mut self, variants: &[VariantDef], types: &[TypeDef]) {
+        for variant in variants {
+            let VariantShape::Tuple(fields) = &variant.shape else { continue; };
+            let [field] = fields.as_slice() else { continue; }; // Longer tuples are already rejected
+            let (holds_fields, path) = match &field.ty {
+                TypeExpr::Map(..) => (true, first_name_path(&field.ty)),
+                TypeExpr::Named { name, path } => match types.iter().find(|t| &t.name == name) {
+                    Some(inner) => (matches!(inner.kind, TypeKind::Struct(_)), path.clone()),
+                    None if PRIMITIVE_TYPES.contains(&name.as_str()) => (false, path.clone()),
+                    None => continue, // Already reported as an unknown type
+                },
+                _ => (false, first_name_path(&field.ty)),
+            };
+            if !holds_fields {
+                self.error(
+                    &path,
+                    &field.context,
+                    String::from("An internally tagged newtype variant has to hold a struct or a map, the tag goes in among its fields"),
+                );
+            }
+        }
+    }
+
+    fn check_type(&mut self, ty: &TypeExpr, defined: &HashSet<&str>, context: &str) {
+        match ty {
+            TypeExpr::Named { name, path } => {
+                if !PRIMITIVE_TYPES.contains(&name.as_str()) && !defined.contains(name.as_str()) {
+                    self.error(path, context, format!("Refers to '{}', which is neither a primitive nor a type in the schema", name));
+                }
+            }
+            TypeExpr::Vec(inner) | TypeExpr::Option(inner) => self.check_type(inner, defined, context),
+            TypeExpr::Map(key, value) => {
+                let key_is_scalar = matches!(key.as_ref(), TypeExpr::Named { name, .. }
+                    if PRIMITIVE_TYPES.contains(&name.as_str()) && name != "f32" && name != "f64");
+                if !key_is_scalar {
+                    self.error(&first_name_path(key), context, String::from("Map keys must be a string, integer, bool or char"));
+                }
+                self.check_type(value, defined, context);
+            }
+        }
+    }
+}
+
+// Containers have no position of their own, the first name inside one is close enough
+fn first_name_path(ty: &TypeExpr) -> String {
+    match ty {
+        TypeExpr::Named { path, .. } => path.clone(),
+        TypeExpr::Vec(inner) | TypeExpr::Option(inner) | TypeExpr::Map(inner, _) => first_name_path(inner),
+    }
+}
+
+// Types held directly by a field or variant, or through an Option, take up space inside the type.
+// A cycle of those would be infinitely big, so each such reference on a cycle is boxed.
+// Vec and map already keep their items on the heap.
+fn boxed_references(types: &[TypeDef]) -> HashSet<(String, String)> {
+    let held = |name: &str| -> Vec<String> {
+        let mut held = Vec::new();
+        if let Some(type_def) = types.iter().find(|t| t.name == name) {
+            for field in type_def.fields() {
+                held_directly(&field.ty, &mut held);
+            }
+        }
+        held
+    };
+    let reaches = |from: &str, to: &str| {
+        let mut seen = HashSet::new();
+        let mut stack = vec![from.to_string()];
+        while let Some(current) = stack.pop() {
+            if current == to {
+                return true;
+            }
+            if seen.insert(current.clone()) {
+                stack.extend(held(&current));
+            }
+        }
+        false
+    };
+
+    let mut boxed = HashSet::new();
+    for type_def in types {
+        for target in held(&type_def.name) {
+            if reaches(&target, &type_def.name) {
+                boxed.insert((type_def.name.clone(), target));
+            }
+        }
+    }
+    boxed
+}
+
+fn held_directly(ty: &TypeExpr, held: &mut Vec<String>) {
+    match ty {
+        TypeExpr::Named { name, .. } => held.push(name.clone()),
+        TypeExpr::Option(inner) => held_directly(inner, held),
+        TypeExpr::Vec(_) | TypeExpr::Map(..) => {}
+    }
+}
+
+fn rust_type(ty: &TypeExpr, is_boxed: &dyn Fn(&str) -> bool) -> String {
+    match ty {
+        TypeExpr::Named { name, .. } if is_boxed(name) => format!("Box<{}>", name),
+        TypeExpr::Named { name, .. } => name.clone(),
+        TypeExpr::Option(inner) => format!("Option<{}>", rust_type(inner, is_boxed)),
+        // Nothing inside a Vec or a map needs boxing
+        TypeExpr::Vec(inner) => format!("Vec<{}>", rust_type(inner, &|_| false)),
+        TypeExpr::Map(key, value) => format!(
+            "std::collections::HashMap<{}, {}>",
+            rust_type(key, &|_| false),
+            rust_type(value, &|_| false)
+        ),
+    }
+}
+
+// The serde derives are spelled out so the generated file needs no imports
+fn derive_paths(derives: &[String]) -> Vec<String> {
+    derives.iter()
+        .map(|derive| match derive.as_str() {
+            "Serialize" | "Deserialize" => format!("serde::{}", derive),
+            _ => derive.clone(),
+        })
+        .collect()
+}
+
+// ASCII only, which is all the generated code needs
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            name != "_" && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn capitalised(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+// Where each value starts in the source, by JSON pointer ("/types/0/fields/make").
+// serde_json's Value forgets positions, so the source is scanned again. It has already
+// parsed, so the scanner can trust it's valid JSON.
+struct Spans {
+    positions: HashMap<String, (usize, usize)>,
+    duplicates: Vec<(String, String, (usize, usize))>, // Object path, repeated key, where the repeat is
+}
+
+impl Spans {
+    fn index(source: &str) -> Spans {
+        let mut scanner = Scanner {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+            spans: Spans { positions: HashMap::new(), duplicates: Vec::new() },
+        };
+        scanner.value(String::new());
+        scanner.spans
+    }
+
+    // Falls back to the nearest enclosing value, for keys that should be there but aren't
+    fn find(&self, path: &str) -> (usize, usize) {
+        let mut path = path;
+        loop {
+            if let Some(position) = self.positions.get(path) {
+                return *position;
+            }
+            match path.rfind('/') {
+                Some(parent) => path = &path[..parent],
+                None => return (1, 1),
+            }
+        }
+    }
+
+    // Drops a value and everything inside it
+    fn forget(&mut self, path: &str) {
+        let inside = |other: &str| other == path || other.strip_prefix(path).is_some_and(|rest| rest.starts_with('/'));
+        self.positions.retain(|other, _| !inside(other));
+        self.duplicates.retain(|(object, _, _)| !inside(object));
+    }
+
+    fn duplicates(&self, path: &str) -> Vec<(String, (usize, usize))> {
+        self.duplicates.iter()
+            .filter(|(object, _, _)| object == path)
+            .map(|(_, key, position)| (key.clone(), *position))
+            .collect()
+    }
+}
+
+struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    spans: Spans,
+}
+
+impl Scanner<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    // An object member is found at its key, so only values with no key record themselves here
+    fn value(&mut self, path: String) {
+        self.skip_whitespace();
+        self.spans.positions.entry(path.clone()).or_insert((self.line, self.column));
+        match self.chars.peek() {
+            Some('{') => self.object(&path),
+            Some('[') => self.array(&path),
+            Some('"') => {
+                self.string();
+            }
+            _ => {
+                while self.chars.peek().is_some_and(|c| !matches!(c, ',' | ']' | '}') && !c.is_whitespace()) {
+                    self.next();
+                }
+            }
+        }
+    }
+
+    fn object(&mut self, path: &str) {
+        self.next();
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                self.next(); // The closing brace
+                return;
+            }
+            let position = (self.line, self.column);
+            let key = self.string();
+            let member = format!("{}/{}", path, key);
+            // serde_json keeps the last of a repeated key, so that's the one whose positions count
+            if self.spans.positions.contains_key(&member) {
+                self.spans.forget(&member);
+                self.spans.duplicates.push((path.to_string(), key, position));
+            }
+            self.spans.positions.insert(member.clone(), position);
+            self.skip_whitespace();
+            self.next(); // The colon
+            self.value(member);
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&',') {
+                self.next();
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str) {
+        self.next();
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&']') {
+                self.next();
+                return;
+            }
+            self.value(format!("{}/{}", path, index));
+            index += 1;
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&',') {
+                self.next();
+            }
+        }
+    }
+
+    // The unescaped contents, so keys match the names serde_json gives back
+    fn string(&mut self) -> String {
+        self.next();
+        let mut text = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                '"' => break,
+                '\\' => match self.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        text.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                    }
+                    Some(other) => text.push(other),
+                    None => break,
+                },
+                _ => text.push(c),
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<String> {
+        match generate(source) {
+            Ok(_) => panic!("Expected the schema to be rejected"),
+            Err(diagnostics) => diagnostics.iter()
+                .inspect(|d| assert_eq!(d.severity, Severity::Error, "{}", d))
+                .map(|d| d.to_string())
+                .collect(),
+        }
+    }
+
+    // Names of the fields or variants of one generated type, in the order they were written out
+    fn members(code: &str, header: &str) -> Vec<String> {
+        let start = code.find(header).unwrap_or_else(|| panic!("No '{}' in the generated code", header));
+        code[start..].lines()
+            .skip(1)
+            .take_while(|line| *line != "}")
+            .filter(|line| line.starts_with("    ") && !line.starts_with("     ") && !line.trim_start().starts_with(['#', '}']))
+            .map(|line| line.trim_start().trim_start_matches("pub ").chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect())
+            .collect()
+    }
+
+    #[test]
+    fn the_crate_schema_generates_without_problems() {
+        let generated = generate(include_str!("test.json")).unwrap();
+        assert!(generated.warnings.is_empty());
+
+        // In the order test.json lists them, not sorted
+        let code = generated.code;
+        assert_eq!(members(&code, "pub struct Car {"), [
+            "make", "model", "year", "top_speed", "vin", "wheels", "spare",
+            "tyre_pressures", "parts", "fuel", "transmission", "owner", "odometer",
+        ]);
+        assert_eq!(members(&code, "pub enum FuelType {"), ["Petrol", "Diesel", "Electric"]);
+        assert_eq!(members(&code, "pub enum Transmission {"), ["Manual", "Automatic", "Cvt"]);
+        assert!(code.find("pub struct Car {") < code.find("pub struct Wheel {"));
+    }
+
+    #[test]
+    fn only_references_held_in_place_on_a_cycle_are_boxed() {
+        let generated = generate(r#"{
+            "types": [
+                { "name": "Node", "fields": {
+                    "next": { "option": "Node" },
+                    "children": { "vec": "Node" },
+                    "by_name": { "map": { "key": "String", "value": "Node" } }
+                } },
+                { "name": "Engine", "fields": { "gearbox": "Gearbox", "badge": "Badge" } },
+                { "name": "Gearbox", "fields": { "engine": { "option": "Engine" } } },
+                { "name": "Badge", "fields": { "text": "String" } }
+            ]
+        }"#).unwrap();
+        let code = generated.code;
+
+        assert!(code.contains("pub next: Option<Box<Node>>,"));
+        assert!(code.contains("pub children: Vec<Node>,"));
+        assert!(code.contains("pub by_name: std::collections::HashMap<String, Node>,"));
+        assert!(code.contains("pub gearbox: Box<Gearbox>,"));
+        assert!(code.contains("pub engine: Option<Box<Engine>>,"));
+        assert!(code.contains("pub badge: Badge,"));
+    }
+
+    #[test]
+    fn broken_json_points_at_the_syntax_error() {
+        assert_eq!(errors(include_str!("fixtures/not_json.json")), ["7:7: Not valid JSON: trailing comma"]);
+    }
+
+    #[test]
+    fn bad_names_are_all_reported_where_they_are() {
+        assert_eq!(errors(include_str!("fixtures/bad_names.json")), [
+            "8:9: Car.type: 'type' is a Rust keyword, name the field something else and use a serde rename",
+            "9:9: Car.top-speed: 'top-speed' is not a valid Rust identifier for a field",
+            "10:9: Car: Field 'make' is listed more than once",
+            "14:7: struct: 'struct' is a Rust keyword, name the type something else and use a serde rename",
+            "18:7: Car: Type 'Car' is already defined on line 5",
+            "22:7: String: 'String' would hide the built in type of the same name",
+        ]);
+    }
+
+    #[test]
+    fn bad_field_types_are_all_reported_where_they_are() {
+        assert_eq!(errors(include_str!("fixtures/bad_types.json")), [
+            "5:7: Garage: Has serde attributes but derives neither Serialize nor Deserialize",
+            "7:19: Garage.cars: Refers to 'Car', which is neither a primitive nor a type in the schema",
+            "8:33: Garage.by_weight: Map keys must be a string, integer, bool or char",
+            "9:9: Garage.owners: Unknown container 'set', expected 'vec', 'option' or 'map'",
+            "10:9: Garage.size: Expected a type name, or an object with one of 'vec', 'option' or 'map'",
+            "13:5: Shed: Has both 'fields' and 'enum', a type is either a struct or an enum",
+            "18:5: Empty: Needs 'fields' for a struct or 'enum' for an enum",
+        ]);
+    }
+
+    #[test]
+    fn bad_enums_are_all_reported_where_they_are() {
+        assert_eq!(errors(include_str!("fixtures/bad_enums.json")), [
+            "4:5: Fuel: Derives Default, so 'default' has to name one of its unit variants",
+            "10:9: Fuel::Hybrid: Tuple variants can't be internally tagged, use named fields or adjacent tagging",
+            "15:7: Gearbox: Unknown tagging 'sideways', expected external, internal, adjacent or untagged",
+            "22:7: Badge: The tag and the content can't both be called 'data'",
+            "23:17: Badge::Text: Expected null, a list of types or an object of fields",
+            "29:19: Licence::Plate: An internally tagged newtype variant has to hold a struct or a map, the tag goes in among its fields",
+            "30:23: Licence::Numbers: An internally tagged newtype variant has to hold a struct or a map, the tag goes in among its fields",
+            "33:18: Licence::Kind: An internally tagged newtype variant has to hold a struct or a map, the tag goes in among its fields",
+        ]);
+    }
+
+    #[test]
+    fn names_and_serde_attributes_that_would_not_compile_are_rejected() {
+        assert_eq!(errors(include_str!("fixtures/bad_serde.json")), [
+            "5:7: Option: 'Option' would hide the Option the generated code uses",
+            "10:18: Car: Serde has no 'bogus' attribute for a struct",
+            "10:60: Car: Serde 'deny_unknown_fields' is a flag, set it to true",
+            "14:22: Car.make: Serde 'rename' needs a string",
+            "14:81: Car.make: Serde has no 'rename_all' attribute for a field",
+            "20:18: Fuel: Set the enum tagging with the 'tagging', 'tag' and 'content' keys instead",
+            "20:33: Fuel: Serde has no 'transparent' attribute for an enum",
+            "24:7: Vec: 'Vec' would hide the Vec the generated code uses",
+            "29:18: Garage: Has serde 'default' but doesn't derive Default",
+            "29:35: Garage: Serde has no 'camelcaseX' case, expected one of lowercase, UPPERCASE, PascalCase, \
+                camelCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case, SCREAMING-KEBAB-CASE",
+            "34:18: Wheel: Serde has no 'Title Case' case, expected one of lowercase, UPPERCASE, PascalCase, \
+                camelCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case, SCREAMING-KEBAB-CASE",
+        ]);
+    }
+
+    #[test]
+    fn repeated_keys_are_checked_where_the_last_one_is() {
+        assert_eq!(errors(include_str!("fixtures/duplicate_keys.json")), [
+            "8:9: Car: Field 'wheels' is listed more than once",
+            "8:21: Car.wheels: Refers to 'Tyre', which is neither a primitive nor a type in the schema",
+            "13:36: Wheel: Field 'radius' is listed more than once",
+        ]);
+    }
+
+    #[test]
+    fn style_problems_are_warnings_and_still_generate() {
+        let generated = generate(include_str!("fixtures/style_warnings.json")).unwrap();
+        assert!(generated.code.contains("pub struct car_part {"));
+        assert!(generated.warnings.iter().all(|w| w.severity.name() == "warning"));
+        let warnings: Vec<String> = generated.warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings, [
+            "2:32: 'Debug' is listed more than once",
+            "5:7: car_part: Type 'car_part' should be UpperCamelCase",
+            "6:7: car_part: Unknown key 'description' is ignored",
+            "7:7: car_part: Unknown key 'tagging' is ignored",
+            "9:9: car_part.partNumber: Field 'partNumber' should be snake_case",
+            "14:7: Colour: 'default' is ignored, the enum doesn't derive Default",
+            "15:30: Colour::dark_blue: Variant 'dark_blue' should be UpperCamelCase",
+        ]);
+    }
+
+    #[test]
+    fn spans_follow_escaped_keys_and_fall_back_to_the_parent() {
+        let spans = Spans::index("{\n  \"a\\\"b\": [1,\n    {\"c\": 2}]\n}");
+        assert_eq!(spans.find("/a\"b"), (2, 3));
+        assert_eq!(spans.find("/a\"b/1/c"), (3, 6));
+        assert_eq!(spans.find("/a\"b/1/missing"), (3, 5));
+        assert_eq!(spans.find("/nothing"), (1, 1));
+    }
+}
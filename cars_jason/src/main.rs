@@ -1,20 +1,75 @@
+use std::collections::HashMap;
+
+// The generator build.rs runs, built here as well so its tests run with the crate's
+#[cfg(test)]
+#[path = "../codegen.rs"]
+mod codegen;
+
 #[allow(dead_code)]
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/generated.rs"));
 }
 
-fn main() {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let wheel = generated::Wheel {
+        radius: 17.5,
+        width: 8.0,
+    };
+
+    // A part can be replaced by another part, so the generator boxes it
+    let old_pump = generated::Part {
+        name: "Water pump".to_string(),
+        components: vec![generated::Part {
+            name: "Impeller".to_string(),
+            components: Vec::new(),
+            replaced_by: None,
+        }],
+        replaced_by: Some(Box::new(generated::Part {
+            name: "Electric water pump".to_string(),
+            components: Vec::new(),
+            replaced_by: None,
+        })),
+    };
+
     let car = generated::Car {
         make: "Toyota".to_string(),
         model: "Camry".to_string(),
         year: 2022.0,
-    };
-
-    let wheel = generated::Wheel {
-        radius: 17.5,
-        width: 8.0,
+        top_speed: 210.0,
+        vin: "4T1B11HK5JU000000".to_string(),
+        wheels: vec![wheel.clone(); 4],
+        spare: Some(generated::Wheel::default()),
+        tyre_pressures: HashMap::from([("front".to_string(), 2.3), ("rear".to_string(), 2.1)]),
+        parts: vec![old_pump],
+        fuel: generated::FuelType::Electric { kwh: 75.0 },
+        transmission: generated::Transmission::Automatic { gears: 8, paddles: true },
+        owner: Some(generated::Owner::Company { name: "Acme Taxis".to_string(), fleet_size: 40 }),
+        odometer: generated::Odometer::Reading(12_500.0, "km".to_string()),
     };
 
     println!("Car: {:?}", car);
     println!("Wheel: {:?}", wheel);
-}
\ No newline at end of file
+
+    // The serde derives and attributes come from test.json
+    let car_json = serde_json::to_string_pretty(&car)?;
+    println!("Car as JSON: {}", car_json);
+    let read_back: generated::Car = serde_json::from_str(&car_json)?;
+    println!("Read back unchanged: {}", read_back == car);
+
+    // Fields marked default can be left out
+    let partial: generated::Car = serde_json::from_str(
+        r#"{
+            "make": "Ford",
+            "model": "Focus",
+            "year": 2019,
+            "wheels": [],
+            "tyrePressures": {},
+            "transmission": "Cvt",
+            "owner": { "kind": "private", "details": "Sam" },
+            "odometer": null
+        }"#,
+    )?;
+    println!("Car with defaults: {:?}", partial);
+
+    Ok(())
+}
@@ -12,6 +12,11 @@ const INITIAL_Q_VELOCITY_MAGNITUDE: f32 = 500.0;
 const VELOCITY_DECAY_RATE: f32 = 0.98;
 const MIN_VELOCITY_THRESHOLD: f32 = 10.0;
 
+// Debug overlay
+const COLLISION_MARKER_LIFETIME: f32 = 0.5; // Seconds of simulated time a collision normal stays visible
+const VELOCITY_ARROW_SCALE: f32 = 0.2;
+const COLLISION_NORMAL_LENGTH: f32 = 25.0;
+
 pub struct Pocket {
     pub position: Vec2,
     pub radius: f32,
@@ -61,10 +66,17 @@ impl Ball {
     }
 }
 
+pub struct CollisionMarker {
+    pub contact_point: Vec2,
+    pub normal: Vec2,
+    pub time_left: f32,
+}
+
 pub struct GameState {
     pub balls: Vec<Ball>,
     pub rectangle_top_left: Vec2,
     pub pockets: Vec<Pocket>,
+    pub collision_markers: Vec<CollisionMarker>,
     rand: ThreadRng,
 }
 
@@ -123,11 +135,17 @@ impl GameState {
             balls,
             rectangle_top_left,
             pockets,
+            collision_markers: Vec::new(),
             rand: thread_rng(),
         }
     }
 
     pub fn update(&mut self, dt: f32) {
+        for marker in &mut self.collision_markers {
+            marker.time_left -= dt;
+        }
+        self.collision_markers.retain(|m| m.time_left > 0.0);
+
         for ball in &mut self.balls {
             ball.update(dt);
         }
@@ -198,6 +216,12 @@ impl GameState {
 
                     let tangent = Vec2::new(-normal.y, normal.x);
 
+                    self.collision_markers.push(CollisionMarker {
+                        contact_point: ball2.position + normal * BALL_RADIUS,
+                        normal,
+                        time_left: COLLISION_MARKER_LIFETIME,
+                    });
+
                     let v1n = v1.dot(normal);
                     let v1t = v1.dot(tangent);
                     let v2n = v2.dot(normal);
@@ -277,4 +301,30 @@ impl GameState {
             BLACK,
         );
     }
-}
\ No newline at end of file
+
+    pub fn draw_debug_overlay(&self) {
+        // Pocket capture radii
+        for pocket in &self.pockets {
+            draw_circle_lines(pocket.position.x, pocket.position.y, pocket.radius, 1.0, ORANGE);
+        }
+
+        // Velocity vectors
+        for ball in self.balls.iter().filter(|b| b.active) {
+            if ball.velocity.length() > 0.0 {
+                let tip = ball.position + ball.velocity * VELOCITY_ARROW_SCALE;
+                draw_line(ball.position.x, ball.position.y, tip.x, tip.y, 2.0, MAGENTA);
+                draw_circle(tip.x, tip.y, 2.5, MAGENTA);
+            }
+        }
+
+        // Collision normals, fading out as they expire
+        for marker in &self.collision_markers {
+            let alpha = (marker.time_left / COLLISION_MARKER_LIFETIME).clamp(0.0, 1.0);
+            let color = Color::new(1.0, 0.0, 0.0, alpha);
+            let start = marker.contact_point - marker.normal * COLLISION_NORMAL_LENGTH;
+            let end = marker.contact_point + marker.normal * COLLISION_NORMAL_LENGTH;
+            draw_line(start.x, start.y, end.x, end.y, 2.0, color);
+            draw_circle(marker.contact_point.x, marker.contact_point.y, 3.0, color);
+        }
+    }
+}
@@ -3,20 +3,32 @@ use macroquad::prelude::*;
 mod game_state;
 use game_state::*;
 
+mod time_control;
+use time_control::*;
+
 #[macroquad::main("Mini Pool Game")]
 async fn main() {
     let mut game_state = GameState::new().await;
+    let mut time_control = TimeControl::new();
 
     loop {
         if is_key_pressed(KeyCode::Space) {
             game_state.shoot_q_ball();
         }
 
-        let dt = get_frame_time(); 
-        game_state.update(dt);
+        time_control.handle_input();
+
+        let dt = time_control.simulation_dt(get_frame_time());
+        if dt > 0.0 {
+            game_state.update(dt);
+        }
 
-        game_state.draw(); 
+        game_state.draw();
+        if time_control.show_debug_overlay {
+            game_state.draw_debug_overlay();
+        }
+        time_control.draw();
 
         next_frame().await
     }
-}
\ No newline at end of file
+}
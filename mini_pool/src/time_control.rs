@@ -0,0 +1,85 @@
+use macroquad::prelude::*;
+
+// Time scales selectable with the [ and ] keys
+const TIME_SCALES: [f32; 7] = [0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 4.0];
+const DEFAULT_TIME_SCALE_INDEX: usize = 3; // 1.0x
+const STEP_DT: f32 = 1.0 / 60.0; // Simulated time advanced by a single step
+
+pub struct TimeControl {
+    pub paused: bool,
+    pub show_debug_overlay: bool,
+    time_scale_index: usize,
+    step_requested: bool,
+}
+
+impl TimeControl {
+    pub fn new() -> Self {
+        Self {
+            paused: false,
+            show_debug_overlay: false,
+            time_scale_index: DEFAULT_TIME_SCALE_INDEX,
+            step_requested: false,
+        }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        TIME_SCALES[self.time_scale_index]
+    }
+
+    pub fn handle_input(&mut self) {
+        if is_key_pressed(KeyCode::P) {
+            self.paused = !self.paused;
+        }
+
+        if is_key_pressed(KeyCode::LeftBracket) && self.time_scale_index > 0 {
+            self.time_scale_index -= 1;
+        }
+        if is_key_pressed(KeyCode::RightBracket) && self.time_scale_index < TIME_SCALES.len() - 1 {
+            self.time_scale_index += 1;
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.time_scale_index = DEFAULT_TIME_SCALE_INDEX;
+        }
+
+        // Stepping only makes sense while paused, so N pauses the game if it was running
+        if is_key_pressed(KeyCode::N) {
+            self.paused = true;
+            self.step_requested = true;
+        }
+
+        if is_key_pressed(KeyCode::D) {
+            self.show_debug_overlay = !self.show_debug_overlay;
+        }
+    }
+
+    // Turns the real frame time into the simulated time for this frame
+    pub fn simulation_dt(&mut self, frame_dt: f32) -> f32 {
+        if self.paused {
+            if self.step_requested {
+                self.step_requested = false;
+                return STEP_DT;
+            }
+            return 0.0;
+        }
+
+        frame_dt * self.time_scale()
+    }
+
+    pub fn draw(&self) {
+        let status = if self.paused { "PAUSED" } else { "RUNNING" };
+        draw_text(
+            &format!("{}  Time Scale: {:.2}x", status, self.time_scale()),
+            10.0,
+            60.0,
+            20.0,
+            BLACK,
+        );
+        draw_text(
+            "P: pause  [ ]: slower/faster  Backspace: 1x  N: step  D: debug overlay",
+            10.0,
+            screen_height() - 10.0,
+            20.0,
+            DARKGRAY,
+        );
+    }
+}
@@ -0,0 +1,13 @@
+#!/bin/sh
+# Builds the browser version into web/, ready to put on any static file server:
+#   ./build_web.sh && python3 -m http.server --directory web 8000
+# then open http://localhost:8000. Needs `rustup target add wasm32-unknown-unknown` once.
+set -e
+cd "$(dirname "$0")"
+
+cargo build --release --target wasm32-unknown-unknown
+cp target/wasm32-unknown-unknown/release/side_pool.wasm web/
+
+# The loader script ships with the macroquad crate, so it always matches the wasm
+macroquad_dir=$(cargo metadata --format-version 1 | sed -n 's/.*"manifest_path":"\([^"]*macroquad-[0-9.]*\)\/Cargo.toml".*/\1/p' | head -n 1)
+cp "$macroquad_dir/js/mq_js_bundle.js" web/
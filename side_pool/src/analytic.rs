@@ -0,0 +1,287 @@
+use macroquad::prelude::*;
+
+use crate::params::PhysicsParams;
+use crate::side_pool::*;
+
+// Plot placement below the table
+const PLOT_TOP: f32 = (TABLE_Y_POS + TABLE_THICKNESS) * PIXELS_PER_METRE + 20.0;
+const PLOT_HEIGHT: f32 = 80.0;
+const PLOT_SAMPLES: usize = 100;
+
+// Closed-form motion of a single ball struck on a flat table.
+// While the contact point slides, kinetic friction decelerates the ball and
+// (if the ball can spin) speeds up its rotation until v = ωR. After that the
+// ball rolls and rolling resistance slows it at a constant 5/7 * μr * g.
+pub struct SlideToRollSolution {
+    pub initial_velocity: f32,
+    pub initial_angular_velocity: f32,
+    pub has_angular_momentum: bool,
+    ball_radius: f32,
+    friction_deceleration: f32,
+    rolling_deceleration: f32,
+}
+
+impl SlideToRollSolution {
+    pub fn new(initial_velocity: f32, initial_angular_velocity: f32, has_angular_momentum: bool, params: &PhysicsParams) -> Self {
+        Self {
+            initial_velocity,
+            initial_angular_velocity,
+            has_angular_momentum,
+            ball_radius: params.ball_radius,
+            friction_deceleration: params.kinetic_friction_coeff * GRAVITY,
+            rolling_deceleration: 5.0 / 7.0 * params.rolling_friction_coeff * GRAVITY,
+        }
+    }
+
+    // Sign of the initial slip velocity at the contact point
+    fn slip_sign(&self) -> f32 {
+        if self.has_angular_momentum {
+            (self.initial_velocity - self.initial_angular_velocity * self.ball_radius).signum()
+        } else {
+            self.initial_velocity.signum()
+        }
+    }
+
+    // Time when sliding ends: pure rolling with angular momentum, a full stop without it
+    pub fn transition_time(&self) -> f32 {
+        if self.has_angular_momentum {
+            let slip_velocity = self.initial_velocity - self.initial_angular_velocity * self.ball_radius;
+            2.0 * slip_velocity.abs() / (7.0 * self.friction_deceleration)
+        } else {
+            self.initial_velocity.abs() / self.friction_deceleration
+        }
+    }
+
+    // Velocity once sliding ends, 5/7 v0 for a centre hit
+    pub fn final_velocity(&self) -> f32 {
+        if self.has_angular_momentum {
+            (5.0 * self.initial_velocity + 2.0 * self.initial_angular_velocity * self.ball_radius) / 7.0
+        } else {
+            0.0
+        }
+    }
+
+    // Distance covered while sliding
+    pub fn slide_distance(&self) -> f32 {
+        self.displacement_at(self.transition_time())
+    }
+
+    // Time spent rolling before rolling resistance brings the ball to rest
+    pub fn rolling_time(&self) -> f32 {
+        self.final_velocity().abs() / self.rolling_deceleration
+    }
+
+    pub fn velocity_at(&self, t: f32) -> f32 {
+        let t_slide = self.transition_time();
+        if t >= t_slide {
+            let t_roll = (t - t_slide).min(self.rolling_time());
+            return self.final_velocity() - self.final_velocity().signum() * self.rolling_deceleration * t_roll;
+        }
+        self.initial_velocity - self.slip_sign() * self.friction_deceleration * t
+    }
+
+    pub fn angular_velocity_at(&self, t: f32) -> f32 {
+        if !self.has_angular_momentum {
+            return 0.0;
+        }
+        if t >= self.transition_time() {
+            return self.velocity_at(t) / self.ball_radius;
+        }
+        self.initial_angular_velocity + self.slip_sign() * 2.5 * self.friction_deceleration / self.ball_radius * t
+    }
+
+    pub fn displacement_at(&self, t: f32) -> f32 {
+        let t_slide = t.min(self.transition_time());
+        let sliding = self.initial_velocity * t_slide
+            - 0.5 * self.slip_sign() * self.friction_deceleration * t_slide * t_slide;
+
+        let t_roll = (t - t_slide).clamp(0.0, self.rolling_time());
+        let rolling = self.final_velocity() * t_roll
+            - 0.5 * self.final_velocity().signum() * self.rolling_deceleration * t_roll * t_roll;
+        sliding + rolling
+    }
+}
+
+pub struct TrajectorySample {
+    pub time: f32,
+    pub velocity: f32,
+}
+
+// Records the simulated cue ball against the analytic solution for the same shot
+pub struct ReferenceComparison {
+    pub solution: SlideToRollSolution,
+    pub start_x: f32,
+    pub samples: Vec<TrajectorySample>,
+    pub max_position_error: f32,
+    pub max_velocity_error: f32,
+    pub max_angular_velocity_error: f32,
+    pub simulated_transition_time: Option<f32>,
+}
+
+impl ReferenceComparison {
+    pub fn new(ball: &Ball, params: &PhysicsParams) -> Self {
+        Self {
+            solution: SlideToRollSolution::new(ball.linear_velocity.x, ball.angular_velocity, ball.has_angular_momentum, params),
+            start_x: ball.position.x,
+            samples: Vec::new(),
+            max_position_error: 0.0,
+            max_velocity_error: 0.0,
+            max_angular_velocity_error: 0.0,
+            simulated_transition_time: None,
+        }
+    }
+
+    pub fn record(&mut self, time: f32, ball: &Ball) {
+        let displacement = ball.position.x - self.start_x;
+        let velocity = ball.linear_velocity.x;
+
+        // Only the sliding phase is compared, cushions and other balls are
+        // outside the analytic model and usually come into play while rolling
+        if time <= self.solution.transition_time() {
+            self.max_position_error = self.max_position_error.max((displacement - self.solution.displacement_at(time)).abs());
+            self.max_velocity_error = self.max_velocity_error.max((velocity - self.solution.velocity_at(time)).abs());
+            self.max_angular_velocity_error = self.max_angular_velocity_error
+                .max((ball.angular_velocity - self.solution.angular_velocity_at(time)).abs());
+        }
+
+        if self.simulated_transition_time.is_none() {
+            let ended_sliding = if ball.has_angular_momentum { !ball.is_slipping } else { ball.is_stopped() };
+            if ended_sliding {
+                self.simulated_transition_time = Some(time);
+            }
+        }
+
+        self.samples.push(TrajectorySample { time, velocity });
+    }
+
+    pub fn draw(&self, screen_view_width: f32, table_draw_offset_x: f32) {
+        // Ghost ball at the analytic position
+        if let Some(last) = self.samples.last() {
+            let ghost_x = table_draw_offset_x + px(self.start_x + self.solution.displacement_at(last.time));
+            let radius = self.solution.ball_radius;
+            draw_circle_lines(ghost_x, px(TABLE_Y_POS - radius), px(radius), 2.0, ORANGE);
+        }
+
+        let plot_left = 40.0;
+        let plot_width = screen_view_width - 60.0;
+        let plot_bottom = PLOT_TOP + PLOT_HEIGHT;
+
+        let last_time = self.samples.last().map_or(0.0, |s| s.time);
+        let time_span = (self.solution.transition_time() * 1.5).max(last_time).max(0.1);
+        let velocity_span = self.solution.initial_velocity.abs().max(0.01);
+
+        let to_screen = |t: f32, v: f32| {
+            Vec2::new(
+                plot_left + t / time_span * plot_width,
+                plot_bottom - (v / velocity_span).clamp(-1.0, 1.0) * PLOT_HEIGHT,
+            )
+        };
+
+        draw_line(plot_left, plot_bottom, plot_left + plot_width, plot_bottom, 1.0, BLACK);
+        draw_line(plot_left, PLOT_TOP, plot_left, plot_bottom, 1.0, BLACK);
+        draw_text("v(t)", plot_left - 35.0, PLOT_TOP + 10.0, 18.0, BLACK);
+
+        // Analytic curve
+        for i in 0..PLOT_SAMPLES {
+            let t1 = time_span * i as f32 / PLOT_SAMPLES as f32;
+            let t2 = time_span * (i + 1) as f32 / PLOT_SAMPLES as f32;
+            let p1 = to_screen(t1, self.solution.velocity_at(t1));
+            let p2 = to_screen(t2, self.solution.velocity_at(t2));
+            draw_line(p1.x, p1.y, p2.x, p2.y, 2.0, ORANGE);
+        }
+
+        // Simulated trajectory
+        for pair in self.samples.windows(2) {
+            let p1 = to_screen(pair[0].time, pair[0].velocity);
+            let p2 = to_screen(pair[1].time, pair[1].velocity);
+            draw_line(p1.x, p1.y, p2.x, p2.y, 1.0, BLUE);
+        }
+
+        let simulated_transition = match self.simulated_transition_time {
+            Some(t) => format!("{:.3} s", t),
+            None => "-".to_string(),
+        };
+        let lines = [
+            "Analytic (orange) vs simulated (blue)".to_string(),
+            format!("Transition: {:.3} s analytic, {} simulated", self.solution.transition_time(), simulated_transition),
+            format!("Final V: {:.3} m/s, slide distance: {:.3} m", self.solution.final_velocity(), self.solution.slide_distance()),
+            format!(
+                "Max error: {:.2} mm, {:.2} mm/s, {:.3} rad/s",
+                self.max_position_error * 1000.0,
+                self.max_velocity_error * 1000.0,
+                self.max_angular_velocity_error
+            ),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(line, plot_left, plot_bottom + 20.0 + 18.0 * i as f32, 18.0, BLACK);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::side_pool::tests::{lone_cue_ball_world, PARAMS};
+
+    #[test]
+    fn centre_hit_rolls_at_five_sevenths_of_initial_velocity() {
+        let solution = SlideToRollSolution::new(1.4, 0.0, true, &PARAMS);
+        assert!((solution.final_velocity() - 1.0).abs() < 1e-5);
+
+        let t = solution.transition_time();
+        let rolling_speed = solution.angular_velocity_at(t) * PARAMS.ball_radius;
+        assert!((rolling_speed - solution.velocity_at(t)).abs() < 1e-5);
+        assert!((solution.velocity_at(t - 1e-4) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rolling_ball_comes_to_rest() {
+        let solution = SlideToRollSolution::new(0.7, 0.7 / PARAMS.ball_radius, true, &PARAMS);
+        let stop_time = solution.rolling_time();
+        let a = 5.0 / 7.0 * PARAMS.rolling_friction_coeff * GRAVITY;
+
+        assert_eq!(solution.velocity_at(stop_time + 10.0), 0.0);
+        assert!((solution.displacement_at(stop_time + 10.0) - 0.7 * 0.7 / (2.0 * a)).abs() < 0.01);
+    }
+
+    #[test]
+    fn slide_distance_matches_constant_deceleration() {
+        let v0 = 1.5;
+        let a = PARAMS.kinetic_friction_coeff * GRAVITY;
+
+        let sliding = SlideToRollSolution::new(v0, 0.0, false, &PARAMS);
+        assert!((sliding.slide_distance() - v0 * v0 / (2.0 * a)).abs() < 1e-4);
+
+        // With spin the slide ends at 5/7 v0, after 2 v0 / 7a
+        let rolling = SlideToRollSolution::new(v0, 0.0, true, &PARAMS);
+        let t = 2.0 * v0 / (7.0 * a);
+        let expected = v0 * t - 0.5 * a * t * t;
+        assert!((rolling.slide_distance() - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ball_already_rolling_does_not_slide() {
+        let v0 = 1.2;
+        let solution = SlideToRollSolution::new(v0, v0 / PARAMS.ball_radius, true, &PARAMS);
+        assert_eq!(solution.transition_time(), 0.0);
+        assert!((solution.final_velocity() - v0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn simulated_slide_tracks_analytic_solution() {
+        let mut world = lone_cue_ball_world(true);
+        world.shoot_q_ball(&crate::cue::CueStrike::default());
+
+        let dt = 1.0 / 120.0;
+        for _ in 0..600 {
+            world.step(dt);
+        }
+
+        let comparison = world.reference.as_ref().unwrap();
+        assert!(comparison.max_velocity_error < 0.01);
+        assert!(comparison.max_position_error < 0.005);
+        assert!(comparison.max_angular_velocity_error < 0.5);
+        let simulated = comparison.simulated_transition_time.unwrap();
+        assert!((simulated - comparison.solution.transition_time()).abs() < 2.0 * dt);
+    }
+}
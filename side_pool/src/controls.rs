@@ -0,0 +1,292 @@
+use macroquad::prelude::*;
+
+const BUTTON_ROWS: usize = 3;
+const BUTTON_GAP: f32 = 4.0;
+const LABEL_SIZE: f32 = 18.0;
+
+// Everything the game does from the keyboard, each one also has a button
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Control {
+    Shoot,
+    TipUp,
+    TipDown,
+    ForceUp,
+    ForceDown,
+    ElevationUp,
+    ElevationDown,
+    Pause,
+    SlowMotion,
+    StepBack,
+    StepForward,
+    Scenarios,
+    PreviousScenario,
+    NextScenario,
+    Analytic,
+    Energy,
+    Parameters,
+    Overlays,
+    VelocityArrows,
+    SpinArrows,
+    ContactArrows,
+    FrictionArrows,
+    ExportEnergy,
+    SaveTrajectories,
+    AddWorld,
+    RemoveWorld,
+    Integrator,
+    Timestep,
+    CompareIntegrators,
+}
+
+// In button order: the cue and its arrows along the top row, time and plots in the middle,
+// the lesson and the worlds along the bottom
+const BUTTONS: [Control; 29] = [
+    Control::Shoot,
+    Control::TipUp,
+    Control::TipDown,
+    Control::ForceUp,
+    Control::ForceDown,
+    Control::ElevationUp,
+    Control::ElevationDown,
+    Control::Overlays,
+    Control::VelocityArrows,
+    Control::SpinArrows,
+    Control::ContactArrows,
+    Control::FrictionArrows,
+    Control::Pause,
+    Control::SlowMotion,
+    Control::StepBack,
+    Control::StepForward,
+    Control::Analytic,
+    Control::Energy,
+    Control::ExportEnergy,
+    Control::SaveTrajectories,
+    Control::Scenarios,
+    Control::PreviousScenario,
+    Control::NextScenario,
+    Control::Parameters,
+    Control::AddWorld,
+    Control::RemoveWorld,
+    Control::Integrator,
+    Control::Timestep,
+    Control::CompareIntegrators,
+];
+
+impl Control {
+    pub fn key(self) -> KeyCode {
+        match self {
+            Control::Shoot => KeyCode::Space,
+            Control::TipUp => KeyCode::Up,
+            Control::TipDown => KeyCode::Down,
+            Control::ForceUp => KeyCode::Right,
+            Control::ForceDown => KeyCode::Left,
+            Control::ElevationUp => KeyCode::W,
+            Control::ElevationDown => KeyCode::S,
+            Control::Pause => KeyCode::K,
+            Control::SlowMotion => KeyCode::O,
+            Control::StepBack => KeyCode::Comma,
+            Control::StepForward => KeyCode::Period,
+            Control::Scenarios => KeyCode::L,
+            Control::PreviousScenario => KeyCode::LeftBracket,
+            Control::NextScenario => KeyCode::RightBracket,
+            Control::Analytic => KeyCode::A,
+            Control::Energy => KeyCode::E,
+            Control::Parameters => KeyCode::P,
+            Control::Overlays => KeyCode::V,
+            Control::VelocityArrows => KeyCode::Key1,
+            Control::SpinArrows => KeyCode::Key2,
+            Control::ContactArrows => KeyCode::Key3,
+            Control::FrictionArrows => KeyCode::Key4,
+            Control::ExportEnergy => KeyCode::C,
+            Control::SaveTrajectories => KeyCode::R,
+            Control::AddWorld => KeyCode::Equal,
+            Control::RemoveWorld => KeyCode::Minus,
+            Control::Integrator => KeyCode::I,
+            Control::Timestep => KeyCode::T,
+            Control::CompareIntegrators => KeyCode::M,
+        }
+    }
+
+    // Shoot, Pause and SaveTrajectories are labelled by the game, they change with its state
+    pub fn label(self) -> &'static str {
+        match self {
+            Control::Shoot => "Shoot",
+            Control::TipUp => "Tip up",
+            Control::TipDown => "Tip down",
+            Control::ForceUp => "Force +",
+            Control::ForceDown => "Force -",
+            Control::ElevationUp => "Elev +",
+            Control::ElevationDown => "Elev -",
+            Control::Pause => "Pause",
+            Control::SlowMotion => "Slow",
+            Control::StepBack => "< Step",
+            Control::StepForward => "Step >",
+            Control::Scenarios => "Scenarios",
+            Control::PreviousScenario => "< Demo",
+            Control::NextScenario => "Demo >",
+            Control::Analytic => "Analytic",
+            Control::Energy => "Energy",
+            Control::Parameters => "Params",
+            Control::Overlays => "Arrows",
+            Control::VelocityArrows => "Velocity",
+            Control::SpinArrows => "Spin",
+            Control::ContactArrows => "Contact",
+            Control::FrictionArrows => "Friction",
+            Control::ExportEnergy => "Save log",
+            Control::SaveTrajectories => "Record",
+            Control::AddWorld => "World +",
+            Control::RemoveWorld => "World -",
+            Control::Integrator => "Method",
+            Control::Timestep => "Timestep",
+            Control::CompareIntegrators => "Compare",
+        }
+    }
+}
+
+// A row of buttons for a browser or a touch screen, where the keys aren't to hand.
+// Every key still works, a tap just stands in for the key press.
+pub struct ControlBar {
+    area: Rect,
+    tapped: Option<Control>,
+    keys_enabled: bool, // Off while a text box has the keyboard, so typing doesn't fire shortcuts
+}
+
+impl ControlBar {
+    pub fn new() -> Self {
+        Self {
+            area: Rect::new(0.0, 0.0, 0.0, 0.0),
+            tapped: None,
+            keys_enabled: true,
+        }
+    }
+
+    // Call once a frame before asking what was pressed
+    pub fn update(&mut self, area: Rect, keys_enabled: bool) {
+        self.area = area;
+        self.keys_enabled = keys_enabled;
+        self.tapped = pointer_pressed().and_then(|point| button_at(area, point));
+    }
+
+    fn key_pressed(&self, key: KeyCode) -> bool {
+        self.keys_enabled && is_key_pressed(key)
+    }
+
+    pub fn pressed(&self, control: Control) -> bool {
+        self.key_pressed(control.key()) || self.tapped == Some(control)
+    }
+
+    pub fn draw(&self, label: impl Fn(Control) -> String) {
+        let held = pointer_down().and_then(|point| button_at(self.area, point));
+        for (control, rect) in layout(self.area) {
+            let fill = if held == Some(control) { GRAY } else { WHITE };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, DARKGRAY);
+
+            let text = label(control);
+            let size = measure_text(&text, None, LABEL_SIZE as u16, 1.0);
+            draw_text(
+                &text,
+                rect.x + (rect.w - size.width) / 2.0,
+                rect.y + (rect.h + size.offset_y) / 2.0,
+                LABEL_SIZE,
+                BLACK,
+            );
+        }
+    }
+}
+
+// Equal buttons filling `area` row by row
+fn layout(area: Rect) -> Vec<(Control, Rect)> {
+    let per_row = BUTTONS.len().div_ceil(BUTTON_ROWS);
+    let width = area.w / per_row as f32;
+    let height = area.h / BUTTON_ROWS as f32;
+    BUTTONS.iter()
+        .enumerate()
+        .map(|(i, control)| {
+            let (row, column) = (i / per_row, i % per_row);
+            let rect = Rect::new(
+                area.x + column as f32 * width + BUTTON_GAP / 2.0,
+                area.y + row as f32 * height + BUTTON_GAP / 2.0,
+                width - BUTTON_GAP,
+                height - BUTTON_GAP,
+            );
+            (*control, rect)
+        })
+        .collect()
+}
+
+fn button_at(area: Rect, point: Vec2) -> Option<Control> {
+    layout(area).into_iter().find(|(_, rect)| rect.contains(point)).map(|(control, _)| control)
+}
+
+// Where a finger or the mouse went down this frame. Macroquad also turns touches into
+// mouse clicks for its own widgets, so while there are touches the mouse is ignored.
+pub fn pointer_pressed() -> Option<Vec2> {
+    let touches = touches();
+    if !touches.is_empty() {
+        return touches.iter().find(|t| t.phase == TouchPhase::Started).map(|t| t.position);
+    }
+    is_mouse_button_pressed(MouseButton::Left).then(|| mouse_position().into())
+}
+
+// Where a finger or the mouse is held down, for dragging
+pub fn pointer_down() -> Option<Vec2> {
+    let touches = touches();
+    if !touches.is_empty() {
+        return touches.iter()
+            .find(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|t| t.position);
+    }
+    is_mouse_button_down(MouseButton::Left).then(|| mouse_position().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_button_fits_in_the_bar_without_overlapping() {
+        let area = Rect::new(0.0, 500.0, 800.0, 108.0);
+        let buttons = layout(area);
+        assert_eq!(buttons.len(), BUTTONS.len());
+        for (i, (_, rect)) in buttons.iter().enumerate() {
+            assert!(rect.x >= area.x && rect.right() <= area.right() + 1e-3);
+            assert!(rect.y >= area.y && rect.bottom() <= area.bottom() + 1e-3);
+            for (_, other) in &buttons[i + 1..] {
+                assert!(!rect.overlaps(other));
+            }
+        }
+    }
+
+    #[test]
+    fn a_tap_finds_the_button_under_it() {
+        let area = Rect::new(0.0, 500.0, 800.0, 108.0);
+        for (control, rect) in layout(area) {
+            assert_eq!(button_at(area, rect.center()), Some(control));
+        }
+        // The gaps between buttons and anything outside the bar do nothing
+        assert_eq!(button_at(area, Vec2::new(1.0, 501.0)), None);
+        assert_eq!(button_at(area, Vec2::new(400.0, 100.0)), None);
+    }
+
+    #[test]
+    fn every_control_has_its_own_key() {
+        for (i, control) in BUTTONS.iter().enumerate() {
+            for other in &BUTTONS[i + 1..] {
+                assert_ne!(control.key(), other.key(), "{:?} and {:?}", control, other);
+            }
+        }
+    }
+
+    #[test]
+    fn typing_in_a_text_box_fires_no_shortcuts() {
+        let mut controls = ControlBar { area: Rect::new(0.0, 0.0, 0.0, 0.0), tapped: None, keys_enabled: false };
+        for control in BUTTONS {
+            assert!(!controls.pressed(control), "{:?}", control);
+        }
+
+        // The buttons still work
+        controls.tapped = Some(Control::Parameters);
+        assert!(controls.pressed(Control::Parameters));
+    }
+}
@@ -0,0 +1,204 @@
+use macroquad::prelude::*;
+
+use crate::controls::{Control, ControlBar};
+use crate::params::PhysicsParams;
+use crate::side_pool::*;
+
+// Tip offsets beyond about half a radius miscue on a real table
+pub const MAX_TIP_OFFSET: f32 = 0.5;
+const TIP_OFFSET_STEP: f32 = 0.05;
+// Tip offset that makes the ball roll straight away, h = 2R/5
+pub const NATURAL_ROLL_TIP_OFFSET: f32 = 0.4;
+
+// Degrees above horizontal, a steep jump cue goes to about 45
+pub const MAX_ELEVATION: f32 = 45.0;
+const ELEVATION_STEP: f32 = 5.0;
+
+const MIN_FORCE_FACTOR: f32 = 0.2;
+const MAX_FORCE_FACTOR: f32 = 5.0; // Hard enough for an elevated cue to hop the ball clear of the cloth
+const FORCE_FACTOR_STEP: f32 = 0.1;
+
+const CUE_LENGTH: f32 = 150.0;
+const CUE_GAP: f32 = 6.0; // Space between the tip and the ball while aiming
+
+// A cue stroke. The tip hits the ball with an impulse J along the cue, its line passing
+// `tip_offset` * radius above (positive) or below (negative) the centre, so the ball leaves with
+// v = J / m and ω = J * h / I. Top gives follow, bottom gives draw, the centre gives stun.
+// An elevated cue drives part of the impulse down into the slate.
+pub struct CueStrike {
+    pub tip_offset: f32,
+    pub force_factor: f32, // Multiple of the default impulse
+    pub elevation: f32,    // Degrees above horizontal
+}
+
+impl Default for CueStrike {
+    fn default() -> Self {
+        Self {
+            tip_offset: 0.0,
+            force_factor: 1.0,
+            elevation: 0.0,
+        }
+    }
+}
+
+impl CueStrike {
+    // Impulse from the cue in N s, the default gives the world's cue ball initial velocity
+    pub fn impulse(&self, params: &PhysicsParams) -> f32 {
+        self.force_factor * params.ball_mass * params.cue_ball_initial_velocity
+    }
+
+    // Along the table
+    pub fn linear_velocity(&self, params: &PhysicsParams) -> f32 {
+        self.impulse(params) * self.elevation.to_radians().cos() / params.ball_mass
+    }
+
+    // Down into the slate
+    pub fn vertical_velocity(&self, params: &PhysicsParams) -> f32 {
+        self.impulse(params) * self.elevation.to_radians().sin() / params.ball_mass
+    }
+
+    pub fn angular_velocity(&self, params: &PhysicsParams) -> f32 {
+        let tip_height = self.tip_offset * params.ball_radius;
+        self.impulse(params) * tip_height / params.moment_of_inertia()
+    }
+
+    pub fn spin_name(&self) -> &'static str {
+        if self.tip_offset.abs() < TIP_OFFSET_STEP / 2.0 {
+            "stun"
+        } else if (self.tip_offset - NATURAL_ROLL_TIP_OFFSET).abs() < TIP_OFFSET_STEP / 2.0 {
+            "natural roll"
+        } else if self.tip_offset > 0.0 {
+            "follow"
+        } else {
+            "draw"
+        }
+    }
+
+    pub fn handle_input(&mut self, controls: &ControlBar) {
+        if controls.pressed(Control::TipUp) {
+            self.tip_offset = (self.tip_offset + TIP_OFFSET_STEP).min(MAX_TIP_OFFSET);
+        }
+        if controls.pressed(Control::TipDown) {
+            self.tip_offset = (self.tip_offset - TIP_OFFSET_STEP).max(-MAX_TIP_OFFSET);
+        }
+        if controls.pressed(Control::ForceUp) {
+            self.force_factor = (self.force_factor + FORCE_FACTOR_STEP).min(MAX_FORCE_FACTOR);
+        }
+        if controls.pressed(Control::ForceDown) {
+            self.force_factor = (self.force_factor - FORCE_FACTOR_STEP).max(MIN_FORCE_FACTOR);
+        }
+        if controls.pressed(Control::ElevationUp) {
+            self.elevation = (self.elevation + ELEVATION_STEP).min(MAX_ELEVATION);
+        }
+        if controls.pressed(Control::ElevationDown) {
+            self.elevation = (self.elevation - ELEVATION_STEP).max(0.0);
+        }
+    }
+
+    // Cue stick lined up behind the cue ball at the chosen tip height and elevation
+    pub fn draw(&self, world: &SidePoolWorld) {
+        if let Some(q_ball) = world.balls.iter().find(|b| b.is_q_ball && b.active) {
+            let ball_radius = px(world.params.ball_radius);
+            let centre = Vec2::new(world.table_draw_offset_x() + px(q_ball.position.x), px(q_ball.position.y));
+            // Along the cue towards the ball, and square to it pointing up
+            let direction = Vec2::new(self.elevation.to_radians().cos(), self.elevation.to_radians().sin());
+            let up = Vec2::new(direction.y, -direction.x);
+
+            let line_offset = self.tip_offset * ball_radius;
+            let contact = centre + up * line_offset - direction * (ball_radius * ball_radius - line_offset * line_offset).sqrt();
+            let tip = contact - direction * CUE_GAP;
+            let butt = tip - direction * CUE_LENGTH;
+            let ferrule = tip - direction * 4.0;
+            draw_line(butt.x, butt.y, tip.x, tip.y, 4.0, BEIGE);
+            draw_line(ferrule.x, ferrule.y, tip.x, tip.y, 4.0, SKYBLUE);
+        }
+    }
+
+    // Velocities shown are for the physics in `params`
+    pub fn draw_hud(&self, x: f32, y: f32, params: &PhysicsParams) {
+        draw_text(
+            &format!(
+                "Tip: {:+.2} R ({})  Force: {:.1}x  Elev: {:.0}°  V: {:.2} m/s  W: {:.1} rad/s",
+                self.tip_offset,
+                self.spin_name(),
+                self.force_factor,
+                self.elevation,
+                self.linear_velocity(params),
+                self.angular_velocity(params)
+            ),
+            x,
+            y,
+            18.0,
+            BLACK,
+        );
+        draw_text("Up/Down: tip height  Left/Right: force  W/S: elevation", x, y + 18.0, 18.0, DARKGRAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::side_pool::tests::{lone_cue_ball_world, PARAMS};
+
+    #[test]
+    fn centre_hit_has_no_spin() {
+        let strike = CueStrike::default();
+        assert_eq!(strike.angular_velocity(&PARAMS), 0.0);
+        assert!((strike.linear_velocity(&PARAMS) - PARAMS.cue_ball_initial_velocity).abs() < 1e-3);
+    }
+
+    #[test]
+    fn natural_roll_height_leaves_no_slip() {
+        let strike = CueStrike { tip_offset: NATURAL_ROLL_TIP_OFFSET, force_factor: 1.3, ..CueStrike::default() };
+        let contact_velocity = strike.linear_velocity(&PARAMS) - strike.angular_velocity(&PARAMS) * PARAMS.ball_radius;
+        assert!(contact_velocity.abs() < 1e-3);
+    }
+
+    #[test]
+    fn low_hit_gives_backspin() {
+        let strike = CueStrike { tip_offset: -0.3, ..CueStrike::default() };
+        assert!(strike.angular_velocity(&PARAMS) < 0.0);
+        assert!(strike.linear_velocity(&PARAMS) > 0.0);
+    }
+
+    #[test]
+    fn elevated_cue_drives_part_of_the_impulse_into_the_slate() {
+        let strike = CueStrike { elevation: 30.0, ..CueStrike::default() };
+        let along = strike.linear_velocity(&PARAMS);
+        let down = strike.vertical_velocity(&PARAMS);
+        assert!((along - PARAMS.cue_ball_initial_velocity * 30.0_f32.to_radians().cos()).abs() < 1e-3);
+        assert!((down - PARAMS.cue_ball_initial_velocity / 2.0).abs() < 1e-3);
+        assert_eq!(CueStrike::default().vertical_velocity(&PARAMS), 0.0);
+    }
+
+    #[test]
+    fn draw_shot_rolls_slower_than_stun_shot() {
+        let rolling_velocity = |strike: CueStrike| {
+            let mut world = lone_cue_ball_world(true);
+            world.shoot_q_ball(&strike);
+            for _ in 0..300 {
+                world.step(1.0 / 60.0);
+            }
+            assert!(!world.balls[0].is_slipping);
+            world.balls[0].linear_velocity.x
+        };
+
+        let stun = rolling_velocity(CueStrike::default());
+        let draw = rolling_velocity(CueStrike { tip_offset: -0.3, ..CueStrike::default() });
+        assert!(draw < stun);
+    }
+
+    #[test]
+    fn draw_shot_comes_back_after_hitting_the_object_ball() {
+        let mut world = SidePoolWorld::headless(1.5, true);
+        // Put the object ball close enough that the backspin is still there at contact
+        world.balls[1].position.x = world.balls[0].position.x + PARAMS.ball_radius * 6.0;
+        world.shoot_q_ball(&CueStrike { tip_offset: -MAX_TIP_OFFSET, ..CueStrike::default() });
+
+        for _ in 0..60 {
+            world.step(1.0 / 60.0);
+        }
+        assert!(world.balls[0].linear_velocity.x < 0.0);
+        assert!(world.balls[1].linear_velocity.x > 0.0);
+    }
+}
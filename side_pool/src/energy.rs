@@ -0,0 +1,244 @@
+use macroquad::prelude::*;
+
+use crate::params::PhysicsParams;
+use crate::side_pool::*;
+
+// Plots sit between the world label and the table
+const PLOT_TOP: f32 = 110.0;
+const PLOT_HEIGHT: f32 = 120.0;
+const MAX_PLOTTED_POINTS: usize = 200;
+
+// One plotted quantity and its line colour
+type PlotSeries = (fn(&EnergySample) -> f32, Color);
+
+pub struct EnergySample {
+    pub time: f32,
+    pub translational_energy: f32,
+    pub rotational_energy: f32,
+    pub potential_energy: f32,
+    pub linear_momentum: f32,
+    pub angular_momentum: f32,
+    pub friction_loss: f32,
+    pub cushion_loss: f32,
+    pub bounce_loss: f32,
+    pub collision_loss: f32,
+    pub pocketed_energy: f32,
+}
+
+impl EnergySample {
+    // Energy the run started with: still moving plus everything already lost
+    pub fn total_energy(&self) -> f32 {
+        self.translational_energy
+            + self.rotational_energy
+            + self.potential_energy
+            + self.friction_loss
+            + self.cushion_loss
+            + self.bounce_loss
+            + self.collision_loss
+            + self.pocketed_energy
+    }
+}
+
+// Energy and momentum of one world over a single run. Losses are measured
+// by the step around each physics phase, so every model change is accounted for.
+#[derive(Default)]
+pub struct EnergyLog {
+    pub samples: Vec<EnergySample>,
+    pub friction_loss: f32,
+    pub cushion_loss: f32,
+    pub bounce_loss: f32,
+    pub collision_loss: f32,
+    pub pocketed_energy: f32,
+}
+
+impl EnergyLog {
+    pub fn record(&mut self, time: f32, balls: &[Ball], params: &PhysicsParams) {
+        let active = || balls.iter().filter(|b| b.active);
+        self.samples.push(EnergySample {
+            time,
+            translational_energy: active().map(|b| b.translational_kinetic_energy(params)).sum(),
+            rotational_energy: active().map(|b| b.rotational_kinetic_energy(params)).sum(),
+            potential_energy: active().map(|b| b.potential_energy(params)).sum(),
+            linear_momentum: active().map(|b| params.ball_mass * b.linear_velocity.x).sum(),
+            angular_momentum: active().map(|b| params.moment_of_inertia() * b.angular_velocity).sum(),
+            friction_loss: self.friction_loss,
+            cushion_loss: self.cushion_loss,
+            bounce_loss: self.bounce_loss,
+            collision_loss: self.collision_loss,
+            pocketed_energy: self.pocketed_energy,
+        });
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "time,translational_energy,rotational_energy,potential_energy,linear_momentum,angular_momentum,friction_loss,cushion_loss,bounce_loss,collision_loss,pocketed_energy\n",
+        );
+        for s in &self.samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                s.time,
+                s.translational_energy,
+                s.rotational_energy,
+                s.potential_energy,
+                s.linear_momentum,
+                s.angular_momentum,
+                s.friction_loss,
+                s.cushion_loss,
+                s.bounce_loss,
+                s.collision_loss,
+                s.pocketed_energy
+            ));
+        }
+        csv
+    }
+
+    pub fn draw(&self, screen_view_width: f32) {
+        let plot_width = (screen_view_width - 60.0) / 2.0;
+        let energy_left = 20.0;
+        let momentum_left = energy_left + plot_width + 20.0;
+
+        // A ball dropping into a pocket trades potential energy for speed, which can dwarf the shot itself
+        let energy_scale = self.samples.iter()
+            .flat_map(|s| [s.total_energy(), s.translational_energy, s.potential_energy.abs()])
+            .fold(0.0, f32::max);
+        let has_negative_energy = self.samples.iter().any(|s| s.potential_energy < 0.0);
+        let linear_scale = self.samples.iter().map(|s| s.linear_momentum.abs()).fold(0.0, f32::max);
+        let angular_scale = self.samples.iter().map(|s| s.angular_momentum.abs()).fold(0.0, f32::max);
+
+        self.draw_series(energy_left, plot_width, energy_scale, has_negative_energy, &[
+            (|s: &EnergySample| s.translational_energy, BLUE),
+            (|s: &EnergySample| s.rotational_energy, DARKPURPLE),
+            (|s: &EnergySample| s.potential_energy, DARKGREEN),
+            (|s: &EnergySample| s.friction_loss, RED),
+            (|s: &EnergySample| s.cushion_loss, ORANGE),
+            (|s: &EnergySample| s.bounce_loss, GOLD),
+            (|s: &EnergySample| s.collision_loss, MAGENTA),
+        ]);
+        self.draw_series(momentum_left, plot_width, linear_scale, true, &[
+            (|s: &EnergySample| s.linear_momentum, BLUE),
+        ]);
+        self.draw_series(momentum_left, plot_width, angular_scale, true, &[
+            (|s: &EnergySample| s.angular_momentum, DARKPURPLE),
+        ]);
+
+        let Some(last) = self.samples.last() else { return; };
+        let legend_y = PLOT_TOP + PLOT_HEIGHT + 14.0;
+        draw_text(
+            &format!(
+                "KE {:.3} rot {:.3} pot {:.3} fric {:.3} cush {:.3} bnc {:.3} col {:.3} J",
+                last.translational_energy,
+                last.rotational_energy,
+                last.potential_energy,
+                last.friction_loss,
+                last.cushion_loss,
+                last.bounce_loss,
+                last.collision_loss
+            ),
+            energy_left,
+            legend_y,
+            14.0,
+            BLACK,
+        );
+        draw_text(
+            &format!("p {:.3} N s (blue) L {:.2e} J s (purple)", last.linear_momentum, last.angular_momentum),
+            momentum_left,
+            legend_y,
+            14.0,
+            BLACK,
+        );
+    }
+
+    // Each series is scaled so `scale` fills the plot, signed plots put zero in the middle
+    fn draw_series(&self, left: f32, width: f32, scale: f32, signed: bool, series: &[PlotSeries]) {
+        let bottom = PLOT_TOP + PLOT_HEIGHT;
+        let zero_y = if signed { PLOT_TOP + PLOT_HEIGHT / 2.0 } else { bottom };
+        draw_rectangle_lines(left, PLOT_TOP, width, PLOT_HEIGHT, 1.0, DARKGRAY);
+        draw_line(left, zero_y, left + width, zero_y, 1.0, GRAY);
+
+        let (Some(first), Some(last)) = (self.samples.first(), self.samples.last()) else { return; };
+        let time_span = (last.time - first.time).max(0.1);
+        let value_span = if signed { scale * 2.0 } else { scale };
+        if value_span <= 0.0 {
+            return;
+        }
+
+        let step = (self.samples.len() / MAX_PLOTTED_POINTS).max(1);
+        for (value, color) in series {
+            let points: Vec<Vec2> = self.samples.iter().step_by(step)
+                .map(|s| Vec2::new(
+                    left + (s.time - first.time) / time_span * width,
+                    zero_y - value(s) / value_span * PLOT_HEIGHT,
+                ))
+                .collect();
+            for pair in points.windows(2) {
+                draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, 1.5, *color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cue::CueStrike;
+    use crate::side_pool::tests::lone_cue_ball_world;
+
+    fn run(world: &mut SidePoolWorld, steps: usize) {
+        world.shoot_q_ball(&CueStrike::default());
+        for _ in 0..steps {
+            world.step(1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn energy_is_accounted_for_throughout_a_run() {
+        let mut world = SidePoolWorld::headless(0.9, true);
+        run(&mut world, 1200);
+
+        let initial = world.energy_log.samples[0].total_energy();
+        for sample in &world.energy_log.samples {
+            assert!((sample.total_energy() - initial).abs() < initial * 1e-3);
+        }
+        assert!(world.energy_log.friction_loss > 0.0);
+    }
+
+    #[test]
+    fn cushion_losses_are_recorded() {
+        let mut world = SidePoolWorld::headless(0.6, false);
+        world.balls.retain(|b| b.is_q_ball);
+        // Hard enough that the slide reaches the far cushion
+        world.set_params(PhysicsParams { cue_ball_initial_velocity: 2.0, ..PhysicsParams::default() });
+        // Close the pockets so the ball meets the cushions
+        for pocket in &mut world.pockets {
+            pocket.width = 0.0;
+        }
+        run(&mut world, 600);
+
+        assert!(world.energy_log.cushion_loss > 0.0);
+        assert_eq!(world.energy_log.collision_loss, 0.0);
+    }
+
+    #[test]
+    fn bounces_are_accounted_for() {
+        let mut world = lone_cue_ball_world(true);
+        world.shoot_q_ball(&CueStrike { elevation: 45.0, force_factor: 5.0, ..CueStrike::default() });
+        for _ in 0..240 {
+            world.step(1.0 / 120.0);
+        }
+
+        let initial = world.energy_log.samples[0].total_energy();
+        let last = world.energy_log.samples.last().unwrap();
+        assert!(world.energy_log.bounce_loss > 0.0);
+        assert!((last.total_energy() - initial).abs() < initial * 1e-3);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_sample() {
+        let mut world = SidePoolWorld::headless(0.9, true);
+        run(&mut world, 10);
+
+        let csv = world.energy_log.to_csv();
+        assert_eq!(csv.lines().count(), world.energy_log.samples.len() + 1);
+        assert!(csv.starts_with("time,"));
+    }
+}
@@ -0,0 +1,208 @@
+// Physics runs at a fixed step whatever the frame rate, these are the choices
+pub const TIMESTEPS: [f32; 5] = [1.0 / 240.0, 1.0 / 120.0, 1.0 / 60.0, 1.0 / 30.0, 1.0 / 15.0];
+pub const DEFAULT_TIMESTEP_INDEX: usize = 1;
+// A long frame (window dragged, debugger) is cut short instead of running hundreds of steps
+const MAX_FRAME_TIME: f32 = 0.25;
+
+// Motion of a ball along the table
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MotionState {
+    pub position: f32,
+    pub velocity: f32,
+    pub angle: f32,
+    pub angular_velocity: f32,
+}
+
+// Time derivative of a MotionState
+struct Rates {
+    velocity: f32,
+    acceleration: f32,
+    angular_velocity: f32,
+    angular_acceleration: f32,
+}
+
+impl MotionState {
+    fn rates(&self, acceleration: &impl Fn(&MotionState) -> (f32, f32)) -> Rates {
+        let (linear, angular) = acceleration(self);
+        Rates {
+            velocity: self.velocity,
+            acceleration: linear,
+            angular_velocity: self.angular_velocity,
+            angular_acceleration: angular,
+        }
+    }
+
+    fn offset(&self, rates: &Rates, dt: f32) -> MotionState {
+        MotionState {
+            position: self.position + rates.velocity * dt,
+            velocity: self.velocity + rates.acceleration * dt,
+            angle: self.angle + rates.angular_velocity * dt,
+            angular_velocity: self.angular_velocity + rates.angular_acceleration * dt,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Integrator {
+    ExplicitEuler,
+    SemiImplicitEuler,
+    Rk4,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 3] = [Integrator::ExplicitEuler, Integrator::SemiImplicitEuler, Integrator::Rk4];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::ExplicitEuler => "Explicit Euler",
+            Integrator::SemiImplicitEuler => "Semi-implicit Euler",
+            Integrator::Rk4 => "RK4",
+        }
+    }
+
+    pub fn next(&self) -> Integrator {
+        let index = Self::ALL.iter().position(|i| i == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    // Advances `state` by dt. `acceleration` gives the linear and angular acceleration for a state.
+    pub fn step(&self, state: MotionState, dt: f32, acceleration: impl Fn(&MotionState) -> (f32, f32)) -> MotionState {
+        match self {
+            // Position moves with the old velocity
+            Integrator::ExplicitEuler => state.offset(&state.rates(&acceleration), dt),
+            // Velocity first, then position moves with the new velocity
+            Integrator::SemiImplicitEuler => {
+                let rates = state.rates(&acceleration);
+                let velocity = state.velocity + rates.acceleration * dt;
+                let angular_velocity = state.angular_velocity + rates.angular_acceleration * dt;
+                MotionState {
+                    position: state.position + velocity * dt,
+                    velocity,
+                    angle: state.angle + angular_velocity * dt,
+                    angular_velocity,
+                }
+            }
+            Integrator::Rk4 => {
+                let k1 = state.rates(&acceleration);
+                let k2 = state.offset(&k1, dt / 2.0).rates(&acceleration);
+                let k3 = state.offset(&k2, dt / 2.0).rates(&acceleration);
+                let k4 = state.offset(&k3, dt).rates(&acceleration);
+                let weighted = |a: f32, b: f32, c: f32, d: f32| (a + 2.0 * b + 2.0 * c + d) / 6.0;
+                state.offset(
+                    &Rates {
+                        velocity: weighted(k1.velocity, k2.velocity, k3.velocity, k4.velocity),
+                        acceleration: weighted(k1.acceleration, k2.acceleration, k3.acceleration, k4.acceleration),
+                        angular_velocity: weighted(k1.angular_velocity, k2.angular_velocity, k3.angular_velocity, k4.angular_velocity),
+                        angular_acceleration: weighted(
+                            k1.angular_acceleration,
+                            k2.angular_acceleration,
+                            k3.angular_acceleration,
+                            k4.angular_acceleration,
+                        ),
+                    },
+                    dt,
+                )
+            }
+        }
+    }
+}
+
+// Turns variable frame times into a whole number of fixed physics steps,
+// carrying the leftover time into the next frame
+pub struct FixedTimestep {
+    pub timestep_index: usize,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self {
+            timestep_index: DEFAULT_TIMESTEP_INDEX,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn dt(&self) -> f32 {
+        TIMESTEPS[self.timestep_index]
+    }
+
+    pub fn cycle(&mut self) {
+        self.timestep_index = (self.timestep_index + 1) % TIMESTEPS.len();
+        self.accumulator = 0.0;
+    }
+
+    // Number of steps of dt() to run this frame
+    pub fn steps(&mut self, frame_dt: f32) -> usize {
+        self.accumulator += frame_dt.min(MAX_FRAME_TIME);
+        let mut steps = 0;
+        while self.accumulator >= self.dt() {
+            self.accumulator -= self.dt();
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: MotionState = MotionState { position: 0.0, velocity: 100.0, angle: 0.0, angular_velocity: 2.0 };
+
+    fn run(integrator: Integrator, steps: usize, dt: f32) -> MotionState {
+        let mut state = START;
+        for _ in 0..steps {
+            state = integrator.step(state, dt, |_| (-20.0, 5.0));
+        }
+        state
+    }
+
+    #[test]
+    fn rk4_is_exact_for_constant_acceleration() {
+        let state = run(Integrator::Rk4, 30, 1.0 / 30.0);
+        assert!((state.position - (100.0 - 0.5 * 20.0)).abs() < 1e-3);
+        assert!((state.velocity - 80.0).abs() < 1e-3);
+        assert!((state.angle - (2.0 + 0.5 * 5.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn euler_schemes_miss_on_either_side() {
+        // Decelerating: explicit Euler uses the faster old velocity, semi-implicit the slower new one
+        let exact = 100.0 - 0.5 * 20.0;
+        let explicit = run(Integrator::ExplicitEuler, 30, 1.0 / 30.0).position;
+        let semi_implicit = run(Integrator::SemiImplicitEuler, 30, 1.0 / 30.0).position;
+        assert!(explicit > exact);
+        assert!(semi_implicit < exact);
+        assert!((explicit - exact - (exact - semi_implicit)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rk4_tracks_state_dependent_acceleration() {
+        // Spring: x'' = -x, x(t) = sin(t) from x = 0, v = 1
+        let mut state = MotionState { position: 0.0, velocity: 1.0, angle: 0.0, angular_velocity: 0.0 };
+        let dt = 0.01;
+        for _ in 0..100 {
+            state = Integrator::Rk4.step(state, dt, |s| (-s.position, 0.0));
+        }
+        assert!((state.position - 1.0_f32.sin()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn fixed_timestep_carries_leftover_time() {
+        let mut timestep = FixedTimestep::new();
+        let dt = timestep.dt();
+        assert_eq!(timestep.steps(dt * 1.5), 1);
+        assert_eq!(timestep.steps(dt * 0.5), 1);
+        assert_eq!(timestep.steps(dt * 0.25), 0);
+    }
+
+    #[test]
+    fn fixed_timestep_steps_are_independent_of_frame_rate() {
+        let total_steps = |frame_dt: f32, frames: usize| {
+            let mut timestep = FixedTimestep::new();
+            (0..frames).map(|_| timestep.steps(frame_dt)).sum::<usize>()
+        };
+        // One second of frames at 30 and 144 fps, give or take a step of rounding
+        assert!(total_steps(1.0 / 30.0, 30).abs_diff(total_steps(1.0 / 144.0, 144)) <= 1);
+    }
+}
@@ -1,10 +1,24 @@
 use macroquad::prelude::*;
 mod side_pool; 
 use side_pool::*; 
+mod analytic;
+mod controls;
+mod cue;
+mod energy;
+mod integrator;
+mod overlay;
+mod params;
+mod scenario;
+mod timeline;
+mod trajectory;
 
 #[macroquad::main("Side Pool Game")]
 async fn main() {
-    let mut game = SidePoolGame::new().await; 
+    let mut game = SidePoolGame::new(default_world_configs()).await; 
+    // side_pool path/to/scenario.json starts with that scenario laid out
+    if let Some(path) = std::env::args().nth(1) {
+        game.load_scenario(std::path::Path::new(&path));
+    }
 
     loop {
         let dt = get_frame_time();
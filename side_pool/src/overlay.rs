@@ -0,0 +1,126 @@
+use macroquad::prelude::*;
+
+use crate::controls::{Control, ControlBar};
+use crate::params::PhysicsParams;
+use crate::side_pool::{px, Ball, GRAVITY};
+
+const VELOCITY_SCALE: f32 = 60.0;  // Arrow pixels per m/s
+const FRICTION_SCALE: f32 = 100.0; // Arrow pixels per ball weight
+const SPIN_SCALE: f32 = 0.1;       // Radians of arc per rad/s
+const MAX_SPIN_ARC: f32 = 1.75 * std::f32::consts::PI;
+const ARROW_HEAD: f32 = 5.0;
+
+// Optional arrows drawn on every ball, showing why a sliding ball ends up rolling:
+// friction at the contact point opposes the slip until the contact point stops
+pub struct SpinOverlays {
+    pub velocity: bool, // Linear velocity from the centre
+    pub spin: bool,     // Arc round the ball, clockwise for topspin when moving right
+    pub contact: bool,  // Velocity of the contact point relative to the cloth
+    pub friction: bool, // Force the cloth puts on the ball
+}
+
+impl SpinOverlays {
+    pub fn new() -> Self {
+        Self {
+            velocity: false,
+            spin: false,
+            contact: false,
+            friction: false,
+        }
+    }
+
+    pub fn handle_input(&mut self, controls: &ControlBar) {
+        if controls.pressed(Control::Overlays) {
+            self.toggle_all();
+        }
+        if controls.pressed(Control::VelocityArrows) {
+            self.velocity = !self.velocity;
+        }
+        if controls.pressed(Control::SpinArrows) {
+            self.spin = !self.spin;
+        }
+        if controls.pressed(Control::ContactArrows) {
+            self.contact = !self.contact;
+        }
+        if controls.pressed(Control::FrictionArrows) {
+            self.friction = !self.friction;
+        }
+    }
+
+    // All on, or all off if any were showing
+    fn toggle_all(&mut self) {
+        let show = !(self.velocity || self.spin || self.contact || self.friction);
+        *self = Self { velocity: show, spin: show, contact: show, friction: show };
+    }
+
+    pub fn draw(&self, ball: &Ball, offset_x: f32, params: &PhysicsParams) {
+        if !ball.active {
+            return;
+        }
+        let centre = Vec2::new(px(ball.position.x) + offset_x, px(ball.position.y));
+        let contact = centre + Vec2::new(0.0, px(params.ball_radius));
+
+        if self.velocity {
+            draw_arrow(centre, centre + ball.linear_velocity * VELOCITY_SCALE, BLUE);
+        }
+        if self.spin && ball.has_angular_momentum {
+            draw_spin_arc(centre, px(params.ball_radius) * 1.4, ball.angular_velocity);
+        }
+        if self.contact {
+            let slip = ball.relative_velocity_at_contact_x(params);
+            draw_arrow(contact, contact + Vec2::new(slip * VELOCITY_SCALE, 0.0), ORANGE);
+        }
+        if self.friction {
+            // Just under the cloth line so it doesn't hide the contact velocity
+            let below = contact + Vec2::new(0.0, 6.0);
+            let weight = params.ball_mass * GRAVITY;
+            let length = ball.friction_force_x(params) / weight * FRICTION_SCALE;
+            draw_arrow(below, below + Vec2::new(length, 0.0), RED);
+        }
+    }
+
+    pub fn legend(&self) -> String {
+        let state = |on: bool| if on { "on" } else { "off" };
+        format!(
+            "1: velocity, blue ({})  2: spin, purple ({})  3: contact slip, orange ({})  4: friction, red ({})  V: all",
+            state(self.velocity),
+            state(self.spin),
+            state(self.contact),
+            state(self.friction)
+        )
+    }
+}
+
+// Nothing is drawn for arrows too short to have a direction
+fn draw_arrow(from: Vec2, to: Vec2, color: Color) {
+    let along = to - from;
+    if along.length() < 1.0 {
+        return;
+    }
+    draw_line(from.x, from.y, to.x, to.y, 2.0, color);
+    let back = -along.normalize() * ARROW_HEAD;
+    let side = back.perp() * 0.5;
+    draw_triangle(to, to + back + side, to + back - side, color);
+}
+
+// Sweeps from the top of the ball in the direction of spin, further for faster spin.
+// Screen y points down, so a positive angle turns clockwise like the ball's rotation dots.
+fn draw_spin_arc(centre: Vec2, radius: f32, angular_velocity: f32) {
+    let sweep = (angular_velocity * SPIN_SCALE).clamp(-MAX_SPIN_ARC, MAX_SPIN_ARC);
+    if sweep.abs() < 0.05 {
+        return;
+    }
+    let segments = 24;
+    let start = -std::f32::consts::FRAC_PI_2;
+    let point = |angle: f32| centre + Vec2::from_angle(angle) * radius;
+    for i in 0..segments {
+        let a = point(start + sweep * i as f32 / segments as f32);
+        let b = point(start + sweep * (i + 1) as f32 / segments as f32);
+        draw_line(a.x, a.y, b.x, b.y, 2.0, DARKPURPLE);
+    }
+    // Head points along the arc at its end
+    let end_angle = start + sweep;
+    let tangent = Vec2::from_angle(end_angle).perp() * sweep.signum();
+    let end = point(end_angle);
+    draw_arrow(end - tangent * ARROW_HEAD, end, DARKPURPLE);
+}
@@ -0,0 +1,357 @@
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use serde::{Deserialize, Serialize};
+
+pub const PRESET_DIR: &str = "presets";
+
+// What the ball radius slider covers, in metres
+pub const BALL_RADIUS_RANGE: Range<f32> = 0.015..0.04;
+
+// How a ball bounces off the cushions at either end of the table
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CushionModel {
+    // Reverses the ball along the table, scaling speed and spin
+    Simple,
+    // Contact at the cushion nose, above the ball's centre on a real table, so the cushion
+    // also pushes the ball down into the slate and its friction changes the spin
+    NoseHeight,
+}
+
+impl CushionModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CushionModel::Simple => "Simple",
+            CushionModel::NoseHeight => "Nose height",
+        }
+    }
+
+    pub fn next(&self) -> CushionModel {
+        match self {
+            CushionModel::Simple => CushionModel::NoseHeight,
+            CushionModel::NoseHeight => CushionModel::Simple,
+        }
+    }
+}
+
+// Physics that can be tuned between runs, one set per world. SI units throughout.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsParams {
+    pub ball_radius: f32,               // m
+    pub ball_mass: f32,                 // kg
+    pub cue_ball_initial_velocity: f32, // m/s, speed of a default strike
+    pub kinetic_friction_coeff: f32,
+    pub rolling_friction_coeff: f32,
+    pub wall_restitution_coeff: f32,
+    pub ball_restitution_coeff: f32,
+    pub ball_friction_coeff: f32,
+    pub jaw_friction_coeff: f32,
+    pub slate_restitution_coeff: f32,
+    pub cushion_model: CushionModel,
+    pub cushion_nose_height: f32, // Above the cloth, in ball radii
+    pub cushion_friction_coeff: f32,
+}
+
+impl PhysicsParams {
+    pub const DEFAULT: PhysicsParams = PhysicsParams {
+        ball_radius: 0.028575, // Regulation 2 1/4 in ball
+        ball_mass: 0.17, // about 6 oz pool bal
+        cue_ball_initial_velocity: 1.0, // A medium pace shot
+        kinetic_friction_coeff: 0.2, // Ball sliding on standard cloth
+        rolling_friction_coeff: 0.01, // About 1/20 of kinetic friction, like real cloth
+        wall_restitution_coeff: 0.8,
+        ball_restitution_coeff: 0.95, // Phenolic balls lose very little in a collision
+        ball_friction_coeff: 0.06, // Between ball surfaces, drives spin transfer
+        jaw_friction_coeff: 0.2, // Between the ball and the pocket jaws, lets spin grip the rim
+        slate_restitution_coeff: 0.5, // The cloth soaks up a lot of a bounce
+        cushion_model: CushionModel::NoseHeight,
+        cushion_nose_height: 1.4, // Regulation 7/5 R, where a level impulse would leave the ball rolling
+        cushion_friction_coeff: 0.2,
+    };
+
+    // Catches values the sliders can't reach but a hand-edited file can, like negative friction
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("ball_radius", self.ball_radius),
+            ("ball_mass", self.ball_mass),
+            ("cushion_nose_height", self.cushion_nose_height),
+        ];
+        let non_negative = [
+            ("cue_ball_initial_velocity", self.cue_ball_initial_velocity),
+            ("kinetic_friction_coeff", self.kinetic_friction_coeff),
+            ("rolling_friction_coeff", self.rolling_friction_coeff),
+            ("ball_friction_coeff", self.ball_friction_coeff),
+            ("jaw_friction_coeff", self.jaw_friction_coeff),
+            ("cushion_friction_coeff", self.cushion_friction_coeff),
+        ];
+        // A restitution above 1 would add energy at every bounce
+        let restitution = [
+            ("wall_restitution_coeff", self.wall_restitution_coeff),
+            ("ball_restitution_coeff", self.ball_restitution_coeff),
+            ("slate_restitution_coeff", self.slate_restitution_coeff),
+        ];
+
+        // Written so NaN fails every check
+        for (name, value) in positive {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(format!("{} is {}, it has to be above 0", name, value));
+            }
+        }
+        for (name, value) in non_negative {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(format!("{} is {}, it can't be negative", name, value));
+            }
+        }
+        for (name, value) in restitution {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} is {}, it has to be between 0 and 1", name, value));
+            }
+        }
+        Ok(())
+    }
+
+    // Solid sphere
+    pub fn moment_of_inertia(&self) -> f32 {
+        0.4 * self.ball_mass * self.ball_radius * self.ball_radius
+    }
+}
+
+impl Default for PhysicsParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn preset_path(name: &str) -> PathBuf {
+    PathBuf::from(PRESET_DIR).join(format!("{}.json", name))
+}
+
+pub fn save_preset(name: &str, params: &PhysicsParams) -> Result<(), Box<dyn Error>> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Preset name '{}' should only use letters, digits, - and _", name).into());
+    }
+    fs::create_dir_all(PRESET_DIR)?;
+    fs::write(preset_path(name), serde_json::to_string_pretty(params)?)?;
+    Ok(())
+}
+
+pub fn load_preset(name: &str) -> Result<PhysicsParams, Box<dyn Error>> {
+    let json = fs::read_to_string(preset_path(name))?;
+    parse_preset(name, &json)
+}
+
+fn parse_preset(name: &str, json: &str) -> Result<PhysicsParams, Box<dyn Error>> {
+    let params: PhysicsParams = serde_json::from_str(json)?;
+    params.validate().map_err(|err| format!("Preset {}: {}", name, err))?;
+    Ok(params)
+}
+
+// Names of the saved presets, sorted
+pub fn list_presets() -> Vec<String> {
+    json_file_names(PRESET_DIR)
+}
+
+// Names of the .json files in a folder without the extension, sorted
+pub fn json_file_names(dir: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new(); };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+// What the panel asked for this frame, beyond editing the selected world's parameters
+pub enum PanelAction {
+    None,
+    ApplyToAll,
+}
+
+// On-screen sliders for the physics of one world at a time
+pub struct ParamsPanel {
+    pub visible: bool,
+    pub selected_world: usize,
+    preset_name: String,
+    editing_name: bool, // The name box has the keyboard until Enter or Done
+    presets: Vec<String>,
+    status: String,
+}
+
+impl ParamsPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            selected_world: 0,
+            preset_name: String::from("my_preset"),
+            editing_name: false,
+            presets: list_presets(),
+            status: String::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.editing_name = false;
+        if self.visible {
+            self.presets = list_presets();
+        }
+    }
+
+    // While this is true the game's keyboard shortcuts wait, the keys are going into the preset name
+    pub fn typing(&self) -> bool {
+        self.visible && self.editing_name
+    }
+
+    // Edits `params` in place, `world_labels` lists every world so the panel can cycle through them
+    pub fn ui(&mut self, world_labels: &[&str], params: &mut PhysicsParams) -> PanelAction {
+        let mut action = PanelAction::None;
+        let world_label = world_labels.get(self.selected_world).copied().unwrap_or("");
+
+        widgets::Window::new(hash!(), vec2(screen_width() - 330.0, 110.0), vec2(320.0, 400.0))
+            .label("Physics Parameters")
+            .ui(&mut root_ui(), |ui| {
+                ui.label(None, &format!("World: {}", world_label));
+                if ui.button(None, "Next world") {
+                    self.selected_world = (self.selected_world + 1) % world_labels.len().max(1);
+                }
+                ui.separator();
+
+                ui.slider(hash!(), "Ball radius (m)", BALL_RADIUS_RANGE, &mut params.ball_radius);
+                ui.slider(hash!(), "Ball mass (kg)", 0.05..0.5, &mut params.ball_mass);
+                ui.slider(hash!(), "Cue velocity (m/s)", 0.1..4.0, &mut params.cue_ball_initial_velocity);
+                ui.slider(hash!(), "Kinetic friction", 0.05..0.5, &mut params.kinetic_friction_coeff);
+                ui.slider(hash!(), "Rolling friction", 0.0..0.05, &mut params.rolling_friction_coeff);
+                ui.slider(hash!(), "Wall restitution", 0.0..1.0, &mut params.wall_restitution_coeff);
+                ui.slider(hash!(), "Ball restitution", 0.0..1.0, &mut params.ball_restitution_coeff);
+                ui.slider(hash!(), "Ball friction", 0.0..0.3, &mut params.ball_friction_coeff);
+                ui.slider(hash!(), "Jaw friction", 0.0..0.6, &mut params.jaw_friction_coeff);
+                ui.slider(hash!(), "Slate restitution", 0.0..0.9, &mut params.slate_restitution_coeff);
+                if ui.button(None, format!("Cushion: {}", params.cushion_model.name()).as_str()) {
+                    params.cushion_model = params.cushion_model.next();
+                }
+                ui.slider(hash!(), "Nose height (R)", 0.8..1.8, &mut params.cushion_nose_height);
+                ui.slider(hash!(), "Cushion friction", 0.0..0.6, &mut params.cushion_friction_coeff);
+
+                if ui.button(None, "Defaults") {
+                    *params = PhysicsParams::default();
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Apply to all worlds") {
+                    action = PanelAction::ApplyToAll;
+                }
+                ui.separator();
+
+                // macroquad can't say whether its text box has focus, so the panel decides. The box only
+                // shows while renaming and holds the keyboard until Enter or Done.
+                let name_id = hash!();
+                if self.editing_name {
+                    ui.set_input_focus(name_id);
+                    ui.input_text(name_id, "Preset", &mut self.preset_name);
+                    if ui.button(None, "Done") || is_key_pressed(KeyCode::Enter) {
+                        self.editing_name = false;
+                        ui.clear_input_focus();
+                    }
+                } else {
+                    ui.label(None, &format!("Preset: {}", self.preset_name));
+                    ui.same_line(0.0);
+                    if ui.button(None, "Rename") {
+                        self.editing_name = true;
+                    }
+                }
+                if ui.button(None, "Save") {
+                    self.editing_name = false;
+                    self.status = match save_preset(&self.preset_name, params) {
+                        Ok(()) => format!("Saved {}", self.preset_name),
+                        Err(err) => err.to_string(),
+                    };
+                    self.presets = list_presets();
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "Load") {
+                    self.editing_name = false;
+                    self.status = match load_preset(&self.preset_name) {
+                        Ok(loaded) => {
+                            *params = loaded;
+                            format!("Loaded {}", self.preset_name)
+                        }
+                        Err(err) => err.to_string(),
+                    };
+                }
+                for preset in &self.presets {
+                    if ui.button(None, preset.as_str()) {
+                        self.preset_name = preset.clone();
+                        self.editing_name = false;
+                    }
+                }
+                ui.label(None, &self.status);
+            });
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_round_trip_through_json() {
+        let params = PhysicsParams {
+            kinetic_friction_coeff: 0.7,
+            ball_radius: 0.03,
+            cushion_model: CushionModel::Simple,
+            ..PhysicsParams::default()
+        };
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(serde_json::from_str::<PhysicsParams>(&json).unwrap(), params);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let params: PhysicsParams = serde_json::from_str(r#"{ "wall_restitution_coeff": 0.5 }"#).unwrap();
+        assert_eq!(params.wall_restitution_coeff, 0.5);
+        assert_eq!(params.ball_mass, PhysicsParams::DEFAULT.ball_mass);
+    }
+
+    #[test]
+    fn presets_with_impossible_physics_are_rejected() {
+        let loaded = parse_preset("sticky", r#"{ "ball_friction_coeff": -0.1 }"#);
+        assert_eq!(loaded.unwrap_err().to_string(), "Preset sticky: ball_friction_coeff is -0.1, it can't be negative");
+
+        assert!(PhysicsParams::DEFAULT.validate().is_ok());
+        assert!(PhysicsParams { ball_radius: 0.0, ..PhysicsParams::DEFAULT }.validate().is_err());
+        assert!(PhysicsParams { wall_restitution_coeff: 1.5, ..PhysicsParams::DEFAULT }.validate().is_err());
+        assert!(PhysicsParams { ball_mass: f32::NAN, ..PhysicsParams::DEFAULT }.validate().is_err());
+    }
+
+    #[test]
+    fn renaming_a_preset_holds_the_keyboard_until_the_panel_closes() {
+        let mut panel = ParamsPanel::new();
+        assert!(!panel.typing());
+        panel.toggle();
+        panel.editing_name = true;
+        assert!(panel.typing());
+        panel.toggle();
+        assert!(!panel.typing());
+        panel.toggle();
+        assert!(!panel.typing());
+    }
+
+    #[test]
+    fn preset_names_are_checked_before_writing() {
+        assert!(save_preset("../escape", &PhysicsParams::default()).is_err());
+        assert!(save_preset("", &PhysicsParams::default()).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, widgets};
+use serde::Deserialize;
+
+use crate::params::PhysicsParams;
+use crate::side_pool::*;
+
+pub const SCENARIO_DIR: &str = "scenarios";
+
+// The web build can't read the scenarios folder, so the shipped scenarios go in the binary
+#[cfg(any(target_arch = "wasm32", test))]
+const BUILT_IN_SCENARIOS: [(&str, &str); 4] = [
+    ("01_sliding_to_rolling", include_str!("../scenarios/01_sliding_to_rolling.json")),
+    ("02_draw_shot", include_str!("../scenarios/02_draw_shot.json")),
+    ("03_newtons_cradle", include_str!("../scenarios/03_newtons_cradle.json")),
+    ("04_drop_and_bounce", include_str!("../scenarios/04_drop_and_bounce.json")),
+];
+
+// A ball as placed by a scenario, in metres along the table from its left edge
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BallSpec {
+    pub x: f32,
+    pub height: f32,           // Metres above the cloth, dropped from there when the run starts
+    pub color: String,         // A name like "white" or "#rrggbb"
+    pub cue_ball: bool,
+    pub velocity: f32,         // Moving already when the run starts, m/s
+    pub angular_velocity: f32, // rad/s, ignored in worlds without angular momentum
+}
+
+impl Default for BallSpec {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            height: 0.0,
+            color: String::from("white"),
+            cue_ball: false,
+            velocity: 0.0,
+            angular_velocity: 0.0,
+        }
+    }
+}
+
+// Sizes left out are scaled to the ball, like the standard pockets
+#[derive(Clone, Debug, Deserialize)]
+pub struct PocketSpec {
+    pub x: f32,
+    pub width: Option<f32>,
+    pub depth: Option<f32>,
+}
+
+// A prepared demonstration: where the balls start, the table and the physics.
+// Anything left out keeps the usual set-up, so a scenario only lists what it changes.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    pub table_width: Option<f32>,      // Metres, fits the grid cell when left out
+    pub pockets: Option<Vec<PocketSpec>>, // An empty list closes every pocket
+    pub balls: Vec<BallSpec>,
+    pub params: Option<PhysicsParams>, // Given to every world when the scenario loads
+    #[serde(default = "use_cue_by_default")]
+    pub use_cue: bool,                 // Without the cue the balls only move as the scenario sets them off
+}
+
+fn use_cue_by_default() -> bool {
+    true
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, Box<dyn Error>> {
+        let json = read_scenario(path)?;
+        let scenario: Scenario = serde_json::from_str(&json)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    // Catches mistakes in hand-written files before they turn into odd physics
+    pub fn validate(&self) -> Result<(), String> {
+        // The radius below has to be a real size for the placement checks to mean anything
+        if let Some(params) = &self.params {
+            params.validate()?;
+        }
+        let radius = self.params.as_ref().map_or(PhysicsParams::DEFAULT.ball_radius, |p| p.ball_radius);
+        if let Some(width) = self.table_width.filter(|w| *w <= 4.0 * radius) {
+            return Err(format!("Table width {} is too short for a ball of radius {}", width, radius));
+        }
+        if self.balls.iter().filter(|b| b.cue_ball).count() > 1 {
+            return Err(String::from("Only one ball can be the cue ball"));
+        }
+
+        let table_width = self.table_width.unwrap_or(f32::INFINITY);
+        for (i, ball) in self.balls.iter().enumerate() {
+            parse_color(&ball.color).map_err(|err| format!("Ball {}: {}", i + 1, err))?;
+            if ball.x < radius || ball.x > table_width - radius {
+                return Err(format!("Ball {} at x = {} is off the table", i + 1, ball.x));
+            }
+            if ball.height < 0.0 {
+                return Err(format!("Ball {} starts below the cloth", i + 1));
+            }
+        }
+        for (i, pocket) in self.pockets.iter().flatten().enumerate() {
+            if pocket.x < 0.0 || pocket.x > table_width {
+                return Err(format!("Pocket {} at x = {} is off the table", i + 1, pocket.x));
+            }
+        }
+        for (i, first) in self.balls.iter().enumerate() {
+            for (j, second) in self.balls.iter().enumerate().skip(i + 1) {
+                let distance = Vec2::new(first.x - second.x, first.height - second.height).length();
+                if distance < 2.0 * radius - 1e-4 {
+                    return Err(format!("Balls {} and {} overlap", i + 1, j + 1));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The scenario's balls, none if it keeps the usual cue ball and object ball
+    pub fn build_balls(&self, has_angular_momentum: bool) -> Vec<Ball> {
+        self.balls.iter()
+            .map(|spec| {
+                let color = parse_color(&spec.color).unwrap_or(WHITE);
+                let mut ball = Ball::new(Vec2::new(spec.x, TABLE_Y_POS), color, spec.cue_ball, has_angular_momentum);
+                ball.initial_height = spec.height;
+                ball.initial_velocity = spec.velocity;
+                if has_angular_momentum {
+                    ball.initial_angular_velocity = spec.angular_velocity;
+                }
+                ball
+            })
+            .collect()
+    }
+
+    pub fn build_pockets(&self, ball_radius: f32) -> Option<Vec<SidePocket>> {
+        let standard = SidePocket::standard(0.0, ball_radius);
+        self.pockets.as_ref().map(|pockets| {
+            pockets.iter()
+                .map(|spec| SidePocket {
+                    x_position: spec.x,
+                    width: spec.width.unwrap_or(standard.width),
+                    depth: spec.depth.unwrap_or(standard.depth),
+                })
+                .collect()
+        })
+    }
+}
+
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let named = match text.to_ascii_lowercase().as_str() {
+        "white" => Some(WHITE),
+        "black" => Some(BLACK),
+        "red" => Some(RED),
+        "yellow" => Some(YELLOW),
+        "blue" => Some(BLUE),
+        "green" => Some(GREEN),
+        "orange" => Some(ORANGE),
+        "purple" => Some(PURPLE),
+        "maroon" => Some(MAROON),
+        "pink" => Some(PINK),
+        _ => None,
+    };
+    if let Some(color) = named {
+        return Ok(color);
+    }
+
+    let hex = text.strip_prefix('#').filter(|h| h.len() == 6 && h.is_ascii());
+    let channel = |i: usize| hex.and_then(|h| u8::from_str_radix(&h[i..i + 2], 16).ok());
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => Ok(Color::from_rgba(r, g, b, 255)),
+        _ => Err(format!("'{}' is not a colour name or #rrggbb", text)),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_scenario(path: &Path) -> Result<String, Box<dyn Error>> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_scenario(path: &Path) -> Result<String, Box<dyn Error>> {
+    let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    BUILT_IN_SCENARIOS.iter()
+        .find(|(built_in, _)| *built_in == name)
+        .map(|(_, json)| json.to_string())
+        .ok_or_else(|| format!("{} is not one of the built in scenarios", name).into())
+}
+
+pub fn scenario_path(name: &str) -> PathBuf {
+    PathBuf::from(SCENARIO_DIR).join(format!("{}.json", name))
+}
+
+// Sorted, so a numbered set plays as a sequence
+#[cfg(not(target_arch = "wasm32"))]
+pub fn list_scenarios() -> Vec<String> {
+    crate::params::json_file_names(SCENARIO_DIR)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn list_scenarios() -> Vec<String> {
+    BUILT_IN_SCENARIOS.iter().map(|(name, _)| name.to_string()).collect()
+}
+
+// Picks a scenario from the scenarios folder
+pub struct ScenarioMenu {
+    pub visible: bool,
+    scenarios: Vec<String>,
+}
+
+impl ScenarioMenu {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            scenarios: list_scenarios(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.scenarios = list_scenarios();
+        }
+    }
+
+    // The scenario after (or before, for a negative step) `current` in the folder, wrapping round
+    pub fn neighbour(&mut self, current: &str, step: isize) -> Option<String> {
+        self.scenarios = list_scenarios();
+        let count = self.scenarios.len() as isize;
+        if count == 0 {
+            return None;
+        }
+        let index = match self.scenarios.iter().position(|s| s == current) {
+            Some(index) => (index as isize + step).rem_euclid(count),
+            None if step < 0 => count - 1,
+            None => 0,
+        };
+        Some(self.scenarios[index as usize].clone())
+    }
+
+    // Name of the scenario clicked this frame
+    pub fn ui(&mut self, current: &str) -> Option<String> {
+        let mut chosen = None;
+        widgets::Window::new(hash!(), vec2(10.0, 110.0), vec2(300.0, 300.0))
+            .label("Scenarios")
+            .ui(&mut root_ui(), |ui| {
+                if self.scenarios.is_empty() {
+                    ui.label(None, &format!("No scenarios in {}/", SCENARIO_DIR));
+                }
+                for scenario in &self.scenarios {
+                    let marker = if scenario == current { "> " } else { "" };
+                    if ui.button(None, format!("{}{}", marker, scenario).as_str()) {
+                        chosen = Some(scenario.clone());
+                    }
+                }
+            });
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::params::json_file_names;
+
+    fn scenario(json: &str) -> Result<Scenario, String> {
+        let scenario: Scenario = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    #[test]
+    fn missing_fields_keep_the_usual_set_up() {
+        let scenario = scenario(r#"{ "name": "Empty" }"#).unwrap();
+        assert!(scenario.use_cue);
+        assert!(scenario.balls.is_empty());
+        assert!(scenario.pockets.is_none());
+        assert!(scenario.params.is_none());
+    }
+
+    #[test]
+    fn bad_scenarios_are_rejected() {
+        let overlapping = r#"{ "balls": [{ "x": 0.3 }, { "x": 0.33 }] }"#;
+        assert!(scenario(overlapping).unwrap_err().contains("overlap"));
+        let off_table = r#"{ "table_width": 0.6, "balls": [{ "x": 0.7 }] }"#;
+        assert!(scenario(off_table).unwrap_err().contains("off the table"));
+        let two_cue_balls = r#"{ "balls": [{ "x": 0.1, "cue_ball": true }, { "x": 0.3, "cue_ball": true }] }"#;
+        assert!(scenario(two_cue_balls).is_err());
+        assert!(scenario(r##"{ "balls": [{ "x": 0.1, "color": "#12345" }] }"##).is_err());
+        let pocket_off_table = r#"{ "table_width": 1.0, "pockets": [{ "x": 1.2 }] }"#;
+        assert!(scenario(pocket_off_table).unwrap_err().contains("Pocket 1"));
+        let negative_friction = r#"{ "params": { "kinetic_friction_coeff": -0.2 } }"#;
+        assert!(scenario(negative_friction).unwrap_err().contains("kinetic_friction_coeff"));
+        let no_radius = r#"{ "params": { "ball_radius": 0.0 }, "balls": [{ "x": 0.3 }, { "x": 0.3 }] }"#;
+        assert!(scenario(no_radius).unwrap_err().contains("ball_radius"));
+    }
+
+    #[test]
+    fn colours_by_name_or_hex() {
+        assert_eq!(parse_color("White").unwrap(), WHITE);
+        assert_eq!(parse_color("#ff8000").unwrap(), Color::from_rgba(255, 128, 0, 255));
+        assert!(parse_color("mauve").is_err());
+    }
+
+    #[test]
+    fn scenario_sets_up_every_world_the_same_way() {
+        let scenario = scenario(
+            r#"{
+                "table_width": 1.2,
+                "pockets": [{ "x": 0.6, "width": 0.08 }],
+                "balls": [
+                    { "x": 0.15, "cue_ball": true, "velocity": 0.8, "angular_velocity": -8 },
+                    { "x": 0.9, "color": "red", "height": 0.1 }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        for has_angular_momentum in [true, false] {
+            let mut world = SidePoolWorld::headless(2.0, has_angular_momentum);
+            world.apply_scenario(&scenario);
+            world.reset();
+            assert_eq!(world.playable_table_width, 1.2);
+            assert_eq!(world.pockets.len(), 1);
+            assert_eq!(world.pockets[0].width, 0.08);
+            assert_eq!(world.balls[0].linear_velocity.x, 0.8);
+            let expected_spin = if has_angular_momentum { -8.0 } else { 0.0 };
+            assert_eq!(world.balls[0].angular_velocity, expected_spin);
+            assert_eq!(world.balls[1].color, RED);
+            assert!(world.balls[1].airborne);
+            assert_eq!(world.balls[1].position.y, TABLE_Y_POS - PhysicsParams::DEFAULT.ball_radius - 0.1);
+        }
+    }
+
+    #[test]
+    fn shipped_scenarios_are_valid() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SCENARIO_DIR);
+        let mut count = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if let Err(err) = Scenario::load(&path) {
+                panic!("{}: {}", path.display(), err);
+            }
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn web_build_has_every_shipped_scenario() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SCENARIO_DIR);
+        let built_in: Vec<&str> = BUILT_IN_SCENARIOS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(built_in, json_file_names(dir.to_str().unwrap()));
+        for (name, json) in BUILT_IN_SCENARIOS {
+            let scenario: Scenario = serde_json::from_str(json).unwrap();
+            assert!(scenario.validate().is_ok(), "{}", name);
+        }
+    }
+}
@@ -1,33 +1,104 @@
+use std::path::Path;
+
 use macroquad::prelude::*;
 
-// Constants
-pub const TABLE_Y_POS: f32 = 300.0; // Brown part of table
-pub const TABLE_THICKNESS: f32 = 50.0; // Green part of table
+use crate::analytic::ReferenceComparison;
+use crate::controls::{pointer_down, Control, ControlBar};
+use crate::cue::CueStrike;
+use crate::energy::EnergyLog;
+use crate::integrator::{FixedTimestep, Integrator, MotionState};
+use crate::overlay::SpinOverlays;
+use crate::params::{CushionModel, PanelAction, ParamsPanel, PhysicsParams};
+use crate::scenario::{scenario_path, Scenario, ScenarioMenu};
+use crate::timeline::{History, Timeline};
+use crate::trajectory::Trajectory;
 
-pub const BALL_RADIUS: f32 = 10.0; 
-pub const BALL_MASS: f32 = 0.17; // in kgs, about 6 oz pool bal
+// The simulation runs in SI units (m, kg, s), only drawing is in pixels.
+// At this scale a regulation ball is 10 px in radius.
+pub const PIXELS_PER_METRE: f32 = 350.0;
 
-pub const CUE_BALL_INITIAL_VELOCITY_MAGNITUDE: f32 = 150.0; 
+// Constants, in metres down from the top of the view
+pub const TABLE_Y_POS: f32 = 300.0 / PIXELS_PER_METRE; // Brown part of table
+pub const TABLE_THICKNESS: f32 = 50.0 / PIXELS_PER_METRE; // Green part of table
 
-// Physics Constants
-pub const KINETIC_FRICTION_COEFF: f32 = 2.0; // Makes spin faster or slower
-pub const ROLLING_FRICTION_COEFF: f32 = 0.005; 
-pub const WALL_RESTITUTION_COEFF: f32 = 0.8; 
+// Physics Constants, the tunable ones live in PhysicsParams
+pub const GRAVITY: f32 = 9.81; // m/s²
 
 // Stopping
-const MIN_VELOCITY_THRESHOLD: f32 = 1.0;
+const MIN_VELOCITY_THRESHOLD: f32 = 0.003; // m/s
 const MIN_ANGULAR_VELOCITY_THRESHOLD: f32 = 0.05; 
-const SLIPPING_THRESHOLD: f32 = 1.0; 
+const ROLLING_TOLERANCE: f32 = 1e-5; // Contact velocity treated as zero, only absorbs rounding
+const CONTACT_TOLERANCE: f32 = 1e-5; // Balls this close count as touching, so resting chains pass hits along
+const MIN_BOUNCE_VELOCITY: f32 = 0.15; // m/s, slower landings just settle onto the cloth
 
-pub const MOMENT_OF_INERTIA: f32 = 0.4 * BALL_MASS * BALL_RADIUS * BALL_RADIUS;
+// Metres to pixels, for drawing
+pub fn px(metres: f32) -> f32 {
+    metres * PIXELS_PER_METRE
+}
 
 // Playable table width for each world view.
 pub const WORLD_TABLE_PLAYABLE_WIDTH_FACTOR: f32 = 0.9;
+// Every world is drawn into a view this tall and scaled to fit its grid cell
+pub const WORLD_VIEW_HEIGHT: f32 = 540.0;
+const HUD_HEIGHT: f32 = 248.0; // Bottom strip for the prompt, cue, key hints and buttons
+const CONTROL_BAR_HEIGHT: f32 = 108.0; // Three rows of buttons along the very bottom, big enough for a finger
 
+const OUTPUT_DIR: &str = "output"; // Exported energy logs and trajectories, kept out of the working directory
+
+// Each world runs this state machine, the game feeds every world the same events:
+// Initial -> Aiming -> Running -> Settled, and Space from Running or Settled resets back to Aiming
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum GamePhase {
-    Initial,    // Game just started or reset
-    SetupShot,  // Spacebar pressed once
-    Running,    // Spacebar preessed twice
+    Initial,  // Game just started or the worlds were rebuilt
+    Aiming,   // Balls racked, the cue can be lined up
+    Running,  // Shot taken, physics stepping
+    Settled,  // Every ball on the table has stopped
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhaseEvent {
+    SpacePressed,
+    BallsStopped,
+}
+
+impl GamePhase {
+    // The phase `event` leads to, None when the event means nothing in this phase
+    pub fn next(self, event: PhaseEvent) -> Option<GamePhase> {
+        match (self, event) {
+            (GamePhase::Initial, PhaseEvent::SpacePressed) => Some(GamePhase::Aiming),
+            (GamePhase::Aiming, PhaseEvent::SpacePressed) => Some(GamePhase::Running),
+            (GamePhase::Running, PhaseEvent::BallsStopped) => Some(GamePhase::Settled),
+            (GamePhase::Running | GamePhase::Settled, PhaseEvent::SpacePressed) => Some(GamePhase::Aiming), // Reset
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GamePhase::Initial => "Initial",
+            GamePhase::Aiming => "Aiming",
+            GamePhase::Running => "Running",
+            GamePhase::Settled => "Settled",
+        }
+    }
+
+    // What Space, or the Shoot button, does next
+    pub fn action(self) -> &'static str {
+        match self {
+            GamePhase::Initial => "Rack",
+            GamePhase::Aiming => "Shoot",
+            GamePhase::Running | GamePhase::Settled => "Reset",
+        }
+    }
+
+    pub fn prompt(self) -> &'static str {
+        match self {
+            GamePhase::Initial => "Press Space or Rack to set out the balls",
+            GamePhase::Aiming => "Line up the cue with the arrow keys or buttons, then Space or Shoot",
+            GamePhase::Running => "Balls rolling, Space or Reset to start again",
+            GamePhase::Settled => "All balls stopped, Space or Reset to start again",
+        }
+    }
 }
 
 pub struct SidePocket {
@@ -37,13 +108,42 @@ pub struct SidePocket {
 }
 
 impl SidePocket {
+    // Near enough the mouth that the cushion has given way to the pocket
     pub fn contains_ball(&self, ball_x: f32, ball_radius: f32) -> bool {
         (ball_x - self.x_position).abs() < (self.width / 2.0 + ball_radius * 0.2)
     }
+
+    pub fn left_edge(&self) -> f32 {
+        self.x_position - self.width / 2.0
+    }
+
+    pub fn right_edge(&self) -> f32 {
+        self.x_position + self.width / 2.0
+    }
+
+    // The cloth stops here, a ball centred over the hole has nothing holding it up
+    pub fn is_over(&self, ball_x: f32) -> bool {
+        ball_x > self.left_edge() && ball_x < self.right_edge()
+    }
+
+    // Resting height of a ball on the bottom of the pocket
+    pub fn bottom_y(&self, ball_radius: f32) -> f32 {
+        TABLE_Y_POS + TABLE_THICKNESS + self.depth - ball_radius
+    }
+
+    // Wider than a ball so it can drop in, and deep enough to hold one
+    pub fn standard(x_position: f32, ball_radius: f32) -> Self {
+        Self { x_position, width: ball_radius * 2.5, depth: ball_radius * 1.5 }
+    }
 }
 
+#[derive(Clone)]
 pub struct Ball {
     pub position: Vec2,
+    pub initial_position: Vec2, // Where reset puts the ball back
+    pub initial_height: f32,    // Above the cloth at the start, the ball drops from there
+    pub initial_velocity: f32,  // Already moving along the table at the start
+    pub initial_angular_velocity: f32,
     pub linear_velocity: Vec2,
     pub angular_velocity: f32, 
     pub angle: f32,            
@@ -52,12 +152,18 @@ pub struct Ball {
     pub active: bool, 
     pub is_slipping: bool, 
     pub has_angular_momentum: bool, 
+    pub airborne: bool,               // Off the cloth, only gravity acts
+    pub pocket_index: Option<usize>,  // Pocket the ball was captured by, it keeps falling until it reaches the bottom
 }
 
 impl Ball {
     pub fn new(position: Vec2, color: Color, is_q_ball: bool, has_angular_momentum: bool) -> Self {
         Self {
             position,
+            initial_position: position,
+            initial_height: 0.0,
+            initial_velocity: 0.0,
+            initial_angular_velocity: 0.0,
             linear_velocity: Vec2::ZERO,
             angular_velocity: 0.0,
             angle: 0.0,
@@ -66,327 +172,928 @@ impl Ball {
             active: true,
             is_slipping: false,
             has_angular_momentum,
+            airborne: false,
+            pocket_index: None,
         }
     }
 
-    pub fn update(&mut self, dt: f32) {
+    pub fn update(&mut self, dt: f32, params: &PhysicsParams, integrator: Integrator) {
         if !self.active { return; }
 
-        let normal_force_magnitude = BALL_MASS * 9.81; 
+        if self.airborne {
+            self.is_slipping = false;
+            self.fly(dt, integrator);
+            return;
+        }
 
         // Determine if ball is stopped before applying friction
-        if self.linear_velocity.length() < MIN_VELOCITY_THRESHOLD && self.angular_velocity.abs() < MIN_ANGULAR_VELOCITY_THRESHOLD {
+        if self.is_stopped() {
             self.linear_velocity = Vec2::ZERO;
             self.angular_velocity = 0.0;
             self.is_slipping = false;
             return; // Ball is stopped
         }
 
-        let friction_force_x: f32;
-        let friction_torque: f32;
+        // Kinetic friction acts while the contact point slides. Sliding can end part way
+        // through the step, in which case the rest of the step is spent rolling.
+        let time_until_slide_ends = self.time_until_slide_ends(params);
+        let slide_time = time_until_slide_ends.min(dt);
+        if slide_time > 0.0 {
+            self.is_slipping = true;
+            self.slide(slide_time, time_until_slide_ends <= dt, params, integrator);
+        }
 
-        // Velocity at the bottom of the ball
-        let relative_velocity_at_contact_x = self.linear_velocity.x - self.angular_velocity * BALL_RADIUS;
+        let roll_time = dt - slide_time;
+        if roll_time > 0.0 {
+            self.is_slipping = false;
+            self.roll(roll_time, params, integrator);
+        }
+    }
 
+    // Velocity at the bottom of the ball
+    pub fn relative_velocity_at_contact_x(&self, params: &PhysicsParams) -> f32 {
         if self.has_angular_momentum {
-            if relative_velocity_at_contact_x.abs() > SLIPPING_THRESHOLD {
-                self.is_slipping = true;
-                let sign = relative_velocity_at_contact_x.signum(); 
-                
-                friction_force_x = -sign * KINETIC_FRICTION_COEFF * normal_force_magnitude;
-                
-                friction_torque = sign * KINETIC_FRICTION_COEFF * normal_force_magnitude * BALL_RADIUS; 
-            } else {
-                self.is_slipping = false;
-                friction_force_x = -self.linear_velocity.x * ROLLING_FRICTION_COEFF;
-                friction_torque = -self.angular_velocity * ROLLING_FRICTION_COEFF * BALL_RADIUS;
+            self.linear_velocity.x - self.angular_velocity * params.ball_radius
+        } else {
+            self.linear_velocity.x // No spin, the contact point moves with the ball
+        }
+    }
 
-                let target_angular_velocity = self.linear_velocity.x / BALL_RADIUS;
-                let angular_correction_rate = 0.1; 
-                self.angular_velocity = lerp(self.angular_velocity, target_angular_velocity, angular_correction_rate);
-            }
+    fn time_until_slide_ends(&self, params: &PhysicsParams) -> f32 {
+        let relative_velocity_at_contact_x = self.relative_velocity_at_contact_x(params);
+        if relative_velocity_at_contact_x.abs() < ROLLING_TOLERANCE {
+            return 0.0;
+        }
+
+        let friction_deceleration = params.kinetic_friction_coeff * GRAVITY;
+        if self.has_angular_momentum {
+            // Friction slows the ball and spins it up at the same time, 1 + mR²/I = 7/2
+            relative_velocity_at_contact_x.abs() / (3.5 * friction_deceleration)
         } else {
-            self.is_slipping = true;
-            let sign = self.linear_velocity.x.signum();
-            friction_force_x = -sign * KINETIC_FRICTION_COEFF * normal_force_magnitude;
-            friction_torque = 0.0; // No angular effects
+            relative_velocity_at_contact_x.abs() / friction_deceleration
+        }
+    }
+
+    // Kinetic friction opposes the slip at the contact point
+    fn kinetic_friction_force_x(&self, params: &PhysicsParams) -> f32 {
+        let normal_force_magnitude = params.ball_mass * GRAVITY;
+        -self.relative_velocity_at_contact_x(params).signum() * params.kinetic_friction_coeff * normal_force_magnitude
+    }
+
+    // Horizontal force from the cloth right now: kinetic friction while sliding, and while rolling
+    // the small force that slows the ball along with the spin
+    pub fn friction_force_x(&self, params: &PhysicsParams) -> f32 {
+        if !self.active || self.airborne || self.is_stopped() {
+            0.0
+        } else if self.relative_velocity_at_contact_x(params).abs() >= ROLLING_TOLERANCE {
+            self.kinetic_friction_force_x(params)
+        } else if self.has_angular_momentum {
+            -self.angular_velocity.signum() * 5.0 / 7.0 * params.rolling_friction_coeff * params.ball_mass * GRAVITY
+        } else {
+            0.0
         }
+    }
 
-        // Apply forces and torques
-        self.linear_velocity.x += (friction_force_x / BALL_MASS) * dt;
+    // The slip direction can't change within the step, so friction is constant throughout
+    fn slide(&mut self, dt: f32, ends_sliding: bool, params: &PhysicsParams, integrator: Integrator) {
+        let friction_force_x = self.kinetic_friction_force_x(params);
+        let linear_acceleration = friction_force_x / params.ball_mass;
+        let angular_acceleration = if self.has_angular_momentum {
+            // Acting at the bottom of the ball, friction pushing back spins it forwards
+            let friction_torque = -friction_force_x * params.ball_radius;
+            friction_torque / params.moment_of_inertia()
+        } else {
+            0.0
+        };
+        self.integrate(dt, linear_acceleration, angular_acceleration, integrator);
+
+        // Land exactly on the crossing point so rounding doesn't leave a tiny slip behind
+        if ends_sliding {
+            if self.has_angular_momentum {
+                self.angular_velocity = self.linear_velocity.x / params.ball_radius;
+            } else {
+                self.linear_velocity.x = 0.0;
+            }
+        }
+    }
+
+    // Rolling keeps v = ωR exactly. Rolling resistance is a torque opposing the spin,
+    // taken about the contact point it slows the ball at 5/7 * μr * g.
+    fn roll(&mut self, dt: f32, params: &PhysicsParams, integrator: Integrator) {
         if self.has_angular_momentum {
-            self.angular_velocity += (friction_torque / MOMENT_OF_INERTIA) * dt;
+            let normal_force_magnitude = params.ball_mass * GRAVITY;
+            let rolling_resistance_torque = -self.angular_velocity.signum() * params.rolling_friction_coeff * normal_force_magnitude * params.ball_radius;
+            let moment_of_inertia_about_contact = params.moment_of_inertia() + params.ball_mass * params.ball_radius * params.ball_radius;
+            let angular_acceleration = rolling_resistance_torque / moment_of_inertia_about_contact;
+
+            // Rolling resistance can stop the ball but never turn it around
+            let time_until_stopped = (self.angular_velocity / angular_acceleration).abs();
+            self.integrate(dt.min(time_until_stopped), angular_acceleration * params.ball_radius, angular_acceleration, integrator);
+            if time_until_stopped <= dt {
+                self.angular_velocity = 0.0;
+            }
+            self.linear_velocity.x = self.angular_velocity * params.ball_radius;
+        } else {
+            self.integrate(dt, 0.0, 0.0, integrator);
         }
+    }
+
+    // No cloth under the ball, so no friction: it keeps its speed and spin while it falls
+    fn fly(&mut self, dt: f32, integrator: Integrator) {
+        self.integrate(dt, 0.0, 0.0, integrator);
+        let vertical = MotionState {
+            position: self.position.y,
+            velocity: self.linear_velocity.y,
+            angle: 0.0,
+            angular_velocity: 0.0,
+        };
+        let next = integrator.step(vertical, dt, |_| (GRAVITY, 0.0));
+        self.position.y = next.position;
+        self.linear_velocity.y = next.velocity;
+    }
+
+    // Moves the ball along the table under constant accelerations
+    fn integrate(&mut self, dt: f32, linear_acceleration: f32, angular_acceleration: f32, integrator: Integrator) {
+        let state = MotionState {
+            position: self.position.x, // Only horizontal movement
+            velocity: self.linear_velocity.x,
+            angle: self.angle,
+            angular_velocity: self.angular_velocity,
+        };
+        let next = integrator.step(state, dt, |_| (linear_acceleration, angular_acceleration));
 
-        self.position.x += self.linear_velocity.x * dt; // Only horizontal movement
+        self.position.x = next.position;
+        self.linear_velocity.x = next.velocity;
         if self.has_angular_momentum {
-            self.angle += self.angular_velocity * dt; 
-            self.angle %= std::f32::consts::TAU; // Keep angle within 0 to 2π
+            self.angular_velocity = next.angular_velocity;
+            self.angle = next.angle % std::f32::consts::TAU; // Keep angle within 0 to 2π
         } else {
             self.angle = 0.0; // No rotation if no angular momentum
         }
     }
 
-    pub fn draw(&self, offset_x: f32) {
-        if !self.active { return; }
+    pub fn translational_kinetic_energy(&self, params: &PhysicsParams) -> f32 {
+        0.5 * params.ball_mass * self.linear_velocity.length_squared()
+    }
+
+    pub fn rotational_kinetic_energy(&self, params: &PhysicsParams) -> f32 {
+        0.5 * params.moment_of_inertia() * self.angular_velocity * self.angular_velocity
+    }
+
+    pub fn kinetic_energy(&self, params: &PhysicsParams) -> f32 {
+        self.translational_kinetic_energy(params) + self.rotational_kinetic_energy(params)
+    }
+
+    // Meets the slate moving down, and bounces back up if it's coming down fast enough to leave the cloth
+    pub fn bounce_off_slate(&mut self, params: &PhysicsParams) {
+        let rebound_velocity = self.linear_velocity.y * params.slate_restitution_coeff;
+        self.position.y = TABLE_Y_POS - params.ball_radius;
+        if rebound_velocity > MIN_BOUNCE_VELOCITY {
+            self.linear_velocity.y = -rebound_velocity;
+            self.airborne = true;
+        } else {
+            self.linear_velocity.y = 0.0;
+            self.airborne = false;
+        }
+    }
 
-        let draw_pos = Vec2::new(self.position.x + offset_x, self.position.y);
+    // Bounces off a fixed point of contact (a pocket jaw or the cushion nose). `normal` points from
+    // the contact into the ball, so the impulse goes through the centre and only friction changes the spin.
+    pub fn bounce_off_point(&mut self, normal: Vec2, friction_coeff: f32, params: &PhysicsParams) {
+        let normal_velocity = self.linear_velocity.dot(normal);
+        if normal_velocity >= 0.0 {
+            return; // Already moving away
+        }
+
+        let normal_impulse = -(1.0 + params.wall_restitution_coeff) * params.ball_mass * normal_velocity;
+        self.linear_velocity += normal * normal_impulse / params.ball_mass;
+
+        if self.has_angular_momentum {
+            // Surface velocity where the ball touches, up to the impulse that stops it sliding
+            let tangent = Vec2::new(-normal.y, normal.x);
+            let contact_offset = -normal * params.ball_radius;
+            let surface_velocity = self.linear_velocity + self.angular_velocity * Vec2::new(-contact_offset.y, contact_offset.x);
+            let slip = surface_velocity.dot(tangent);
+            let friction_impulse = -slip.signum()
+                * (friction_coeff * normal_impulse).min(2.0 / 7.0 * params.ball_mass * slip.abs());
+            self.linear_velocity += tangent * friction_impulse / params.ball_mass;
+            self.angular_velocity -= friction_impulse * params.ball_radius / params.moment_of_inertia();
+        }
+    }
+
+    // Relative to resting on the cloth, negative once the ball drops below it
+    pub fn potential_energy(&self, params: &PhysicsParams) -> f32 {
+        let height = TABLE_Y_POS - params.ball_radius - self.position.y;
+        params.ball_mass * GRAVITY * height
+    }
+
+    pub fn reset(&mut self) {
+        self.position = self.initial_position;
+        self.linear_velocity = Vec2::new(self.initial_velocity, 0.0);
+        self.angular_velocity = self.initial_angular_velocity;
+        self.angle = 0.0;
+        self.is_slipping = false;
+        self.active = true;
+        self.airborne = self.initial_height > 0.0;
+        self.pocket_index = None;
+    }
+
+    // Uses the same thresholds as update, balls without angular momentum never spin.
+    // A ball in the air is never stopped, even at the top of its arc.
+    pub fn is_stopped(&self) -> bool {
+        !self.airborne
+            && self.linear_velocity.length() < MIN_VELOCITY_THRESHOLD
+            && (!self.has_angular_momentum || self.angular_velocity.abs() < MIN_ANGULAR_VELOCITY_THRESHOLD)
+    }
+
+    // `offset_x` is in pixels, where the table starts in the view
+    pub fn draw(&self, offset_x: f32, ball_radius: f32) {
+        if !self.active && self.pocket_index.is_none() { return; }
+
+        let draw_pos = Vec2::new(px(self.position.x) + offset_x, px(self.position.y));
+        let ball_radius = px(ball_radius);
 
         // Create the ball
-        draw_circle(draw_pos.x, draw_pos.y, BALL_RADIUS, self.color);
+        draw_circle(draw_pos.x, draw_pos.y, ball_radius, self.color);
 
         // Make rotation indicator dots
         if self.is_q_ball && self.has_angular_momentum {
             let num_dots = 4;
             for i in 0..num_dots {
                 let dot_angle = self.angle + (std::f32::consts::TAU / num_dots as f32) * i as f32;
-                let dot_offset_x = dot_angle.cos() * BALL_RADIUS * 0.7; 
-                let dot_offset_y = dot_angle.sin() * BALL_RADIUS * 0.7;
-                draw_circle(draw_pos.x + dot_offset_x, draw_pos.y + dot_offset_y, BALL_RADIUS * 0.15, BLACK);
+                let dot_offset_x = dot_angle.cos() * ball_radius * 0.7; 
+                let dot_offset_y = dot_angle.sin() * ball_radius * 0.7;
+                draw_circle(draw_pos.x + dot_offset_x, draw_pos.y + dot_offset_y, ball_radius * 0.15, BLACK);
             }
         }
 
         // Draw slipping indicator if it's slipping
         if self.is_slipping {
-            draw_circle(draw_pos.x, draw_pos.y, BALL_RADIUS * 0.4, RED);
+            draw_circle(draw_pos.x, draw_pos.y, ball_radius * 0.4, RED);
         }
     }
 }
 
-fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    a + (b - a) * t
+// Two distinct balls from the same list, borrowed mutably at once
+fn pair_mut(balls: &mut [Ball], first: usize, second: usize) -> (&mut Ball, &mut Ball) {
+    if first < second {
+        let (left_part, right_part) = balls.split_at_mut(second);
+        (&mut left_part[first], &mut right_part[0])
+    } else {
+        let (left_part, right_part) = balls.split_at_mut(first);
+        (&mut right_part[0], &mut left_part[second])
+    }
+}
+
+// How long ago two neighbouring balls (left then right) touched, None if they aren't in contact
+// and closing. Older contacts happened earlier in the step and are resolved first.
+fn time_since_contact(left: &Ball, right: &Ball, params: &PhysicsParams) -> Option<f32> {
+    let distance = right.position.x - left.position.x;
+    let combined_radii = params.ball_radius * 2.0;
+    let closing_velocity = left.linear_velocity.x - right.linear_velocity.x;
+
+    // A ball dropping into a pocket can pass over or under its neighbour
+    if (right.position.y - left.position.y).abs() >= combined_radii {
+        return None;
+    }
+
+    if distance < combined_radii + CONTACT_TOLERANCE && closing_velocity > 0.0 {
+        Some((combined_radii - distance).max(0.0) / closing_velocity)
+    } else {
+        None
+    }
+}
+
+// Collision between two neighbouring balls (left then right) that are closing.
+// The normal impulse conserves momentum and loses energy according to the ball restitution coefficient.
+// Spinning balls also rub where they touch: their surfaces slide past each other vertically,
+// and friction there changes both spins, up to the ball friction coefficient times the normal impulse.
+fn resolve_collision(left: &mut Ball, right: &mut Ball, params: &PhysicsParams) {
+    let moment_of_inertia = params.moment_of_inertia();
+    let distance = right.position.x - left.position.x;
+    let overlap = (params.ball_radius * 2.0 - distance).max(0.0);
+    left.position.x -= overlap / 2.0;
+    right.position.x += overlap / 2.0;
+
+    let left_mass = params.ball_mass;
+    let right_mass = params.ball_mass;
+    let reduced_mass = left_mass * right_mass / (left_mass + right_mass);
+
+    let closing_velocity = left.linear_velocity.x - right.linear_velocity.x;
+    let normal_impulse = (1.0 + params.ball_restitution_coeff) * reduced_mass * closing_velocity;
+    left.linear_velocity.x -= normal_impulse / left_mass;
+    right.linear_velocity.x += normal_impulse / right_mass;
+
+    if left.has_angular_momentum && right.has_angular_momentum {
+        // Upward velocity of the left ball's front relative to the right ball's back,
+        // forward spin moves the front of a ball down and the back up
+        let sliding_velocity = -(left.angular_velocity + right.angular_velocity) * params.ball_radius;
+
+        // Impulse that would stop the sliding, both spins change so it is shared as I / 2R²
+        let sticking_impulse = -sliding_velocity * moment_of_inertia / (2.0 * params.ball_radius * params.ball_radius);
+        // Never below zero, f32::clamp panics when the bounds cross
+        let max_friction_impulse = (params.ball_friction_coeff * normal_impulse).max(0.0);
+        let friction_impulse = sticking_impulse.clamp(-max_friction_impulse, max_friction_impulse);
+
+        // The friction pair acts at opposite sides of the two balls, so both spins change the same way
+        let angular_velocity_change = -friction_impulse * params.ball_radius / moment_of_inertia;
+        left.angular_velocity += angular_velocity_change;
+        right.angular_velocity += angular_velocity_change;
+    }
+}
+
+// What a world needs to be rebuilt in a new grid cell
+#[derive(Clone)]
+pub struct WorldConfig {
+    pub label: String,
+    pub has_angular_momentum: bool,
+    pub params: PhysicsParams,
+    pub integrator: Integrator,
+}
+
+impl WorldConfig {
+    pub fn new(label: &str, has_angular_momentum: bool, params: PhysicsParams) -> Self {
+        Self {
+            label: label.to_string(),
+            has_angular_momentum,
+            params,
+            integrator: Integrator::SemiImplicitEuler,
+        }
+    }
+}
+
+// The original side by side comparison
+pub fn default_world_configs() -> Vec<WorldConfig> {
+    vec![
+        WorldConfig::new("With Angular Momentum", true, PhysicsParams::default()),
+        WorldConfig::new("Without Angular Momentum", false, PhysicsParams::default()),
+    ]
+}
+
+// Splits `area` into a near-square grid with a cell per world, filled row by row
+pub fn grid_cells(count: usize, area: Rect) -> Vec<Rect> {
+    let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+    let rows = count.div_ceil(columns).max(1);
+    let cell_width = area.w / columns as f32;
+    let cell_height = area.h / rows as f32;
+
+    (0..count)
+        .map(|i| Rect::new(
+            area.x + (i % columns) as f32 * cell_width,
+            area.y + (i / columns) as f32 * cell_height,
+            cell_width,
+            cell_height,
+        ))
+        .collect()
 }
 
 pub struct SidePoolWorld {
     pub balls: Vec<Ball>,
-    pub screen_x_offset: f32,
+    pub label: String,
+    pub has_angular_momentum: bool,
+    pub viewport: Rect,            // Grid cell on the screen, in pixels
     pub game_phase: GamePhase,
-    initial_q_ball_pos: Vec2,
-    initial_colored_ball_pos: Vec2,
-    pub playable_table_width: f32, // Width of the pool table
-    pub screen_view_width: f32,    // The total width on the screen
-    pockets: Vec<SidePocket>,      // Pockets for pool table
+    pub playable_table_width: f32, // Width of the pool table, in metres
+    pub screen_view_width: f32,    // The total width of the view, in pixels
+    pub view_height: f32,          // In pixels, taller than usual when a long table is fitted into the cell
+    pub pockets: Vec<SidePocket>,  // Pockets for pool table
+    pub elapsed_time: f32,         // Time since the last shot
+    pub reference: Option<ReferenceComparison>, // Analytic solution for the last shot
+    pub energy_log: EnergyLog,     // Energy and momentum since the last shot
+    pub trajectory: Trajectory,    // Every ball's motion since the last shot
+    pub history: History,          // Snapshots since the last shot, for scrubbing back
+    pub params: PhysicsParams,     // Tunable physics, changed between runs
+    pub integrator: Integrator,    // How ball motion is advanced each step
+    pub use_cue: bool,             // Off when a scenario sets the balls moving by itself
 }
 
 impl SidePoolWorld {
-    pub fn new(screen_x_offset: f32, has_angular_momentum: bool, screen_view_width: f32) -> Self {
-        let playable_table_width = screen_view_width * WORLD_TABLE_PLAYABLE_WIDTH_FACTOR;
+    // The view keeps the cell's aspect ratio, so wide cells get longer tables
+    pub fn new(config: &WorldConfig, viewport: Rect) -> Self {
+        let screen_view_width = WORLD_VIEW_HEIGHT * viewport.w / viewport.h;
+        let playable_table_width = screen_view_width * WORLD_TABLE_PLAYABLE_WIDTH_FACTOR / PIXELS_PER_METRE;
+
+        let mut world = Self {
+            label: config.label.clone(),
+            viewport,
+            screen_view_width,
+            integrator: config.integrator,
+            ..Self::headless(playable_table_width, config.has_angular_momentum)
+        };
+        world.set_params(config.params.clone());
+        world
+    }
 
-        let q_ball_y = TABLE_Y_POS - BALL_RADIUS;
-        let colored_ball_y = TABLE_Y_POS - BALL_RADIUS;
+    pub fn config(&self) -> WorldConfig {
+        WorldConfig {
+            integrator: self.integrator,
+            ..WorldConfig::new(&self.label, self.has_angular_momentum, self.params.clone())
+        }
+    }
+
+    // Builds a world from the table width alone, without any screen layout,
+    // so the physics can be run and tested without opening a window.
+    pub fn headless(playable_table_width: f32, has_angular_momentum: bool) -> Self {
+        let ball_radius = PhysicsParams::default().ball_radius;
+        let q_ball_y = TABLE_Y_POS - ball_radius;
+        let colored_ball_y = TABLE_Y_POS - ball_radius;
 
         let q_ball_x = playable_table_width * 0.1; 
         let colored_ball_x = playable_table_width * 0.9; 
 
-        let mut balls = Vec::new();
-        balls.push(Ball::new(Vec2::new(q_ball_x, q_ball_y), WHITE, true, has_angular_momentum));
-        //Purple ball
-        balls.push(Ball::new(Vec2::new(colored_ball_x, colored_ball_y), PURPLE, false, has_angular_momentum)); // Ensure purple ball is added
+        let balls = vec![
+            Ball::new(Vec2::new(q_ball_x, q_ball_y), WHITE, true, has_angular_momentum),
+            //Purple ball
+            Ball::new(Vec2::new(colored_ball_x, colored_ball_y), PURPLE, false, has_angular_momentum),
+        ];
+
+        Self::with_balls(playable_table_width, balls)
+    }
 
-        let pocket_width = BALL_RADIUS * 2.5; // Pockets wider than balls
-        let pocket_depth = BALL_RADIUS * 1.5; 
+    // Pockets are placed at the very edges of the playable table area
+    fn end_pockets(playable_table_width: f32, ball_radius: f32) -> Vec<SidePocket> {
+        vec![
+            SidePocket::standard(0.0, ball_radius),
+            SidePocket::standard(playable_table_width, ball_radius),
+        ]
+    }
 
-        // Pockets are placed at the very edges of the playable table area
-        let pockets = vec![
-            SidePocket { x_position: 0.0, width: pocket_width, depth: pocket_depth }, 
-            SidePocket { x_position: playable_table_width, width: pocket_width, depth: pocket_depth }, 
-        ];
+    // Builds a headless world with any number of balls, in any order
+    pub fn with_balls(playable_table_width: f32, balls: Vec<Ball>) -> Self {
+        let params = PhysicsParams::default();
+        let pockets = Self::end_pockets(playable_table_width, params.ball_radius);
 
         Self {
+            has_angular_momentum: balls.iter().any(|b| b.has_angular_momentum),
             balls,
-            screen_x_offset,
+            label: String::new(),
+            viewport: Rect::new(0.0, 0.0, px(playable_table_width), WORLD_VIEW_HEIGHT),
             game_phase: GamePhase::Initial,
-            initial_q_ball_pos: Vec2::new(q_ball_x, q_ball_y),
-            initial_colored_ball_pos: Vec2::new(colored_ball_x, colored_ball_y),
             playable_table_width,
-            screen_view_width,
+            screen_view_width: px(playable_table_width),
+            view_height: WORLD_VIEW_HEIGHT,
             pockets,
+            elapsed_time: 0.0,
+            reference: None,
+            energy_log: EnergyLog::default(),
+            trajectory: Trajectory::default(),
+            history: History::default(),
+            params,
+            integrator: Integrator::SemiImplicitEuler,
+            use_cue: true,
+        }
+    }
+
+    // New physics for the next run. Balls are put back on the cloth, or at their starting
+    // height above it, in case the radius changed. Pockets are sized for the ball, so they
+    // grow and shrink with it and a bigger ball still fits between the jaws.
+    pub fn set_params(&mut self, params: PhysicsParams) {
+        let scale = params.ball_radius / self.params.ball_radius;
+        for pocket in &mut self.pockets {
+            pocket.width *= scale;
+            pocket.depth *= scale;
         }
+        for ball in &mut self.balls {
+            ball.initial_position.y = TABLE_Y_POS - params.ball_radius - ball.initial_height;
+            if ball.active {
+                ball.position.y = if ball.airborne { ball.initial_position.y } else { TABLE_Y_POS - params.ball_radius };
+            }
+        }
+        self.params = params;
     }
 
+    // Lays the table out as the scenario describes, keeping this world's physics
+    pub fn apply_scenario(&mut self, scenario: &Scenario) {
+        if let Some(table_width) = scenario.table_width {
+            self.fit_table(table_width);
+        }
+        let ball_radius = self.params.ball_radius;
+        self.pockets = scenario.build_pockets(ball_radius)
+            .unwrap_or_else(|| Self::end_pockets(self.playable_table_width, ball_radius));
+
+        let balls = scenario.build_balls(self.has_angular_momentum);
+        if !balls.is_empty() {
+            self.balls = balls;
+        } else if scenario.table_width.is_some() {
+            self.balls = Self::headless(self.playable_table_width, self.has_angular_momentum).balls;
+        }
+        self.use_cue = scenario.use_cue;
+
+        self.set_params(self.params.clone());
+        self.reset();
+    }
+
+    // Moves the world to a new cell after the window is resized. The table and the run carry on
+    // as they were, only the view is refitted around them.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        self.fit_table(self.playable_table_width);
+    }
+
+    // Widens the view for a long table, and makes it taller to keep the cell's aspect ratio
+    fn fit_table(&mut self, table_width: f32) {
+        let fitted_view_width = WORLD_VIEW_HEIGHT * self.viewport.w / self.viewport.h;
+        self.playable_table_width = table_width;
+        self.screen_view_width = (px(table_width) / WORLD_TABLE_PLAYABLE_WIDTH_FACTOR).max(fitted_view_width);
+        self.view_height = self.screen_view_width * self.viewport.h / self.viewport.w;
+    }
+
+    // Steps the physics while Running and notices when this world has settled
     pub fn update(&mut self, dt: f32) {
-        if matches!(self.game_phase, GamePhase::Running) {
-            for ball in &mut self.balls {
-                ball.update(dt);
+        if self.game_phase == GamePhase::Running {
+            self.step(dt);
+            if self.is_settled() {
+                // Settling has nothing to do on the way in, so the strike isn't needed
+                if let Some(next) = self.game_phase.next(PhaseEvent::BallsStopped) {
+                    self.game_phase = next;
+                }
             }
-            self.handle_wall_collisions(); // horizontal wall collisions 
-            self.handle_ball_collisions();
-            self.handle_pocketing(); 
         }
     }
 
+    // Moves through the state machine, racking or shooting on the way into a phase
+    pub fn handle(&mut self, event: PhaseEvent, strike: &CueStrike) {
+        let Some(next) = self.game_phase.next(event) else { return; };
+        match next {
+            GamePhase::Aiming => self.reset(),
+            GamePhase::Running => self.shoot_q_ball(strike),
+            GamePhase::Initial | GamePhase::Settled => {}
+        }
+        self.game_phase = next;
+    }
+
+    // Advances the physics by dt regardless of the game phase
+    pub fn step(&mut self, dt: f32) {
+        // Energy is measured around each phase so the log knows where it went
+        let energy_before = self.mechanical_energy();
+        for ball in &mut self.balls {
+            ball.update(dt, &self.params, self.integrator);
+        }
+        let energy_after_friction = self.mechanical_energy();
+        self.handle_wall_collisions(); // horizontal wall collisions 
+        self.handle_pocket_edges();
+        let energy_after_cushions = self.mechanical_energy();
+        self.handle_slate_contacts();
+        let energy_after_bounces = self.mechanical_energy();
+        self.handle_ball_collisions();
+        let energy_after_collisions = self.mechanical_energy();
+        self.handle_pocketing(); 
+        let energy_after_pocketing = self.mechanical_energy();
+        self.animate_pocketed_balls(dt);
+
+        self.energy_log.friction_loss += energy_before - energy_after_friction;
+        self.energy_log.cushion_loss += energy_after_friction - energy_after_cushions;
+        self.energy_log.bounce_loss += energy_after_cushions - energy_after_bounces;
+        self.energy_log.collision_loss += energy_after_bounces - energy_after_collisions;
+        self.energy_log.pocketed_energy += energy_after_collisions - energy_after_pocketing;
+
+        self.elapsed_time += dt;
+        self.energy_log.record(self.elapsed_time, &self.balls, &self.params);
+        self.trajectory.record(self.elapsed_time, &self.balls);
+        self.history.record(self.elapsed_time, &self.balls);
+        if let (Some(reference), Some(q_ball)) = (&mut self.reference, self.balls.iter().find(|b| b.is_q_ball)) {
+            reference.record(self.elapsed_time, q_ball);
+        }
+    }
+
+    // Kinetic plus potential energy of every ball still on the table
+    pub fn mechanical_energy(&self) -> f32 {
+        self.balls.iter()
+            .filter(|b| b.active)
+            .map(|b| b.kinetic_energy(&self.params) + b.potential_energy(&self.params))
+            .sum()
+    }
+
+    // True once every ball on the table has come to rest and every potted ball has hit the bottom
+    pub fn is_settled(&self) -> bool {
+        self.balls.iter()
+            .filter(|b| b.active || b.pocket_index.is_some())
+            .all(|b| b.is_stopped())
+    }
+
     fn handle_wall_collisions(&mut self) {
+        match self.params.cushion_model {
+            CushionModel::Simple => self.handle_simple_cushions(),
+            CushionModel::NoseHeight => self.handle_cushion_noses(),
+        }
+    }
+
+    fn handle_simple_cushions(&mut self) {
         let left_table_edge = 0.0;
         let right_table_edge = self.playable_table_width;
+        let ball_radius = self.params.ball_radius;
+        let wall_restitution_coeff = self.params.wall_restitution_coeff;
+        let pockets = &self.pockets;
+        let over_pocket = |x: f32| pockets.iter().any(|p| p.contains_ball(x, ball_radius));
 
         for ball in &mut self.balls {
             if !ball.active { continue; }
 
-            if ball.position.x - BALL_RADIUS < left_table_edge && !self.pockets[0].contains_ball(ball.position.x, BALL_RADIUS) {
-                ball.position.x = left_table_edge + BALL_RADIUS;
-                ball.linear_velocity.x *= -WALL_RESTITUTION_COEFF; // Bounce
+            // Balls past the end of the table have already gone over a pocket and stay out there
+            if ball.position.x - ball_radius < left_table_edge && ball.position.x > left_table_edge && !over_pocket(ball.position.x) {
+                ball.position.x = left_table_edge + ball_radius;
+                ball.linear_velocity.x *= -wall_restitution_coeff; // Bounce
                 ball.linear_velocity.x *= (0.5_f32).sqrt(); // Collision
                 if ball.has_angular_momentum {
-                    ball.angular_velocity *= -WALL_RESTITUTION_COEFF; 
+                    ball.angular_velocity *= -wall_restitution_coeff; 
                 }
             }
-            else if ball.position.x + BALL_RADIUS > right_table_edge && !self.pockets[1].contains_ball(ball.position.x, BALL_RADIUS) {
-                ball.position.x = right_table_edge - BALL_RADIUS;
-                ball.linear_velocity.x *= -WALL_RESTITUTION_COEFF; // Bounce
+            else if ball.position.x + ball_radius > right_table_edge && ball.position.x < right_table_edge && !over_pocket(ball.position.x) {
+                ball.position.x = right_table_edge - ball_radius;
+                ball.linear_velocity.x *= -wall_restitution_coeff; // Bounce
                 ball.linear_velocity.x *= (0.5_f32).sqrt(); // Collision
                 if ball.has_angular_momentum {
-                    ball.angular_velocity *= -WALL_RESTITUTION_COEFF; 
+                    ball.angular_velocity *= -wall_restitution_coeff; 
                 }
             }
         }
     }
 
+    // The ball touches the cushion at its nose, which sits above the centre of a ball on the cloth.
+    // A ball in the air can be level with the nose, or clear it altogether.
+    fn handle_cushion_noses(&mut self) {
+        let ball_radius = self.params.ball_radius;
+        let nose_y = TABLE_Y_POS - self.params.cushion_nose_height * ball_radius;
+        let pockets = &self.pockets;
+        let over_pocket = |x: f32| pockets.iter().any(|p| p.contains_ball(x, ball_radius));
+
+        for ball in self.balls.iter_mut().filter(|b| b.active) {
+            let height_above_centre = ball.position.y - nose_y;
+            if height_above_centre.abs() >= ball_radius {
+                continue;
+            }
+            // How far the contact is from the centre along the table
+            let reach = (ball_radius * ball_radius - height_above_centre * height_above_centre).sqrt();
+
+            // Balls past the end of the table have already gone over a pocket and stay out there
+            let edge_x = if ball.position.x - reach < 0.0 && ball.position.x > 0.0 && !over_pocket(ball.position.x) {
+                ball.position.x = reach;
+                0.0
+            } else if ball.position.x + reach > self.playable_table_width
+                && ball.position.x < self.playable_table_width
+                && !over_pocket(ball.position.x)
+            {
+                ball.position.x = self.playable_table_width - reach;
+                self.playable_table_width
+            } else {
+                continue;
+            };
+
+            let normal = (ball.position - Vec2::new(edge_x, nose_y)) / ball_radius;
+            ball.bounce_off_point(normal, self.params.cushion_friction_coeff, &self.params);
+        }
+    }
+
+    // Balls only move along x, so only neighbours can touch. Contacts are resolved one at a
+    // time, earliest first, so a hit travels down a chain of touching balls like a Newton's cradle.
     fn handle_ball_collisions(&mut self) {
-        if self.balls.len() < 2 { return; }
+        let mut order: Vec<usize> = (0..self.balls.len()).filter(|&i| self.balls[i].active).collect();
+        order.sort_by(|&a, &b| self.balls[a].position.x.total_cmp(&self.balls[b].position.x));
+
+        // Every resolved contact stops that pair approaching, so this always finishes well before the limit
+        for _ in 0..order.len() * order.len() {
+            let earliest = order.windows(2)
+                .filter_map(|pair| {
+                    time_since_contact(&self.balls[pair[0]], &self.balls[pair[1]], &self.params).map(|t| (pair[0], pair[1], t))
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2));
+
+            let Some((left_idx, right_idx, _)) = earliest else { break; };
+            let (left, right) = pair_mut(&mut self.balls, left_idx, right_idx);
+            resolve_collision(left, right, &self.params);
+        }
+    }
 
-        let q_ball_idx = self.balls.iter().position(|b| b.is_q_ball).unwrap();
-        let other_ball_idx = self.balls.iter().position(|b| !b.is_q_ball).unwrap();
+    // Each side of a pocket is a jaw: a rounded top corner at cloth height with a vertical face below.
+    // Balls bounce off them with the wall restitution, and spin grips them through jaw friction,
+    // so a ball that reaches the far jaw too high or too fast skips over it or is thrown back out.
+    fn handle_pocket_edges(&mut self) {
+        let ball_radius = self.params.ball_radius;
+
+        for ball in self.balls.iter_mut().filter(|b| b.active) {
+            for pocket in &self.pockets {
+                for edge_x in [pocket.left_edge(), pocket.right_edge()] {
+                    // Closest point of the jaw: the corner, or the face when the ball is below the cloth
+                    let jaw_point = Vec2::new(edge_x, ball.position.y.max(TABLE_Y_POS));
+                    let offset = ball.position - jaw_point;
+                    let distance = offset.length();
+                    if distance >= ball_radius || distance == 0.0 {
+                        continue;
+                    }
+
+                    // Moving away means rolling over the edge, which bounce_off_point leaves alone
+                    let normal = offset / distance;
+                    ball.position = jaw_point + normal * ball_radius;
+                    ball.bounce_off_point(normal, self.params.jaw_friction_coeff, &self.params);
+                }
+            }
+        }
+    }
 
-        let (ball1, ball2) = {
-            let (mut_ref1, mut_ref2);
-            if q_ball_idx < other_ball_idx {
-                let (left_part, right_part) = self.balls.split_at_mut(other_ball_idx);
-                mut_ref1 = &mut left_part[q_ball_idx];
-                mut_ref2 = &mut right_part[0];
-            } else {
-                let (left_part, right_part) = self.balls.split_at_mut(q_ball_idx);
-                mut_ref1 = &mut left_part[other_ball_idx];
-                mut_ref2 = &mut right_part[0];
+    // Balls with no cloth under them fall, and bounce off the slate when they come back down on it
+    fn handle_slate_contacts(&mut self) {
+        let resting_y = TABLE_Y_POS - self.params.ball_radius;
+        for ball in self.balls.iter_mut().filter(|b| b.active) {
+            if self.pockets.iter().any(|p| p.is_over(ball.position.x)) {
+                ball.airborne = true;
+            } else if ball.airborne && ball.position.y >= resting_y {
+                ball.bounce_off_slate(&self.params);
+            } else if !ball.airborne && ball.linear_velocity.y != 0.0 {
+                // A cushion nose above the centre drives the ball into the slate, one below lifts it off
+                if ball.linear_velocity.y > 0.0 {
+                    ball.bounce_off_slate(&self.params);
+                } else {
+                    ball.airborne = true;
+                }
             }
-            (mut_ref1, mut_ref2)
-        };
+        }
+    }
 
-        let distance = (ball1.position.x - ball2.position.x).abs();
-        let combined_radii = BALL_RADIUS * 2.0;
+    // A ball is potted once its top drops below the cloth, nothing can bring it back from there.
+    // Balls thrown clear over the far jaw of an end pocket go off the table.
+    fn handle_pocketing(&mut self) {
+        let ball_radius = self.params.ball_radius;
+        let rail_width = (self.screen_view_width / PIXELS_PER_METRE - self.playable_table_width) / 2.0;
+        let table_left = self.pockets.iter().map(|p| p.left_edge()).fold(0.0, f32::min) - rail_width;
+        let table_right = self.pockets.iter().map(|p| p.right_edge()).fold(self.playable_table_width, f32::max) + rail_width;
+
+        for ball in self.balls.iter_mut().filter(|b| b.active) {
+            let captured_by = self.pockets.iter()
+                .position(|p| p.is_over(ball.position.x) && ball.position.y - ball_radius > TABLE_Y_POS);
+            if let Some(pocket_index) = captured_by {
+                ball.active = false; // Mark ball as potted
+                ball.pocket_index = Some(pocket_index);
+                ball.angular_velocity = 0.0; // Stop spin
+                ball.is_slipping = false;
+            } else if ball.position.x < table_left || ball.position.x > table_right {
+                ball.active = false;
+            }
+        }
+    }
 
-        let relative_velocity_x = ball1.linear_velocity.x - ball2.linear_velocity.x;
-        let is_approaching = (ball2.position.x - ball1.position.x).signum() == relative_velocity_x.signum();
+    // Potted balls keep falling, rattling between the pocket walls, until they land on the bottom
+    fn animate_pocketed_balls(&mut self, dt: f32) {
+        let ball_radius = self.params.ball_radius;
+        let restitution = self.params.wall_restitution_coeff;
 
-        if distance < combined_radii && is_approaching {
-            let overlap = combined_radii - distance;
-            let sign_dir = (ball1.position.x - ball2.position.x).signum();
-            ball1.position.x += sign_dir * (overlap / 2.0);
-            ball2.position.x -= sign_dir * (overlap / 2.0);
+        for ball in &mut self.balls {
+            let Some(pocket) = ball.pocket_index.map(|i| &self.pockets[i]) else { continue; };
+            if !ball.airborne {
+                continue; // Already at the bottom
+            }
 
-            // Collision
-            let transfer_factor = 0.9; // How much of velocity the q ball gives to purple ball
-            let cue_ball_retained_velocity = ball1.linear_velocity.x * (1.0 - transfer_factor);
-            let blue_ball_gains_velocity = ball1.linear_velocity.x * transfer_factor;
+            ball.linear_velocity.y += GRAVITY * dt;
+            ball.position += ball.linear_velocity * dt;
 
-            ball1.linear_velocity.x = cue_ball_retained_velocity;
-            ball2.linear_velocity.x = blue_ball_gains_velocity;
+            let (left, right) = (pocket.left_edge() + ball_radius, pocket.right_edge() - ball_radius);
+            if ball.position.x < left || ball.position.x > right {
+                ball.position.x = ball.position.x.clamp(left.min(right), right.max(left));
+                ball.linear_velocity.x *= -restitution;
+            }
 
-            // Transfer angular momentum during collision
-            if ball1.has_angular_momentum && ball2.has_angular_momentum {
-                let avg_angular_vel = (ball1.angular_velocity + ball2.angular_velocity) / 2.0;
-                ball1.angular_velocity = avg_angular_vel * 0.5; 
-                ball2.angular_velocity = avg_angular_vel * 0.5;
-            } else if ball1.has_angular_momentum {
-                ball1.angular_velocity *= 0.5; 
+            let bottom = pocket.bottom_y(ball_radius);
+            if ball.position.y >= bottom {
+                ball.position.y = bottom;
+                ball.linear_velocity = Vec2::ZERO;
+                ball.airborne = false;
             }
         }
     }
 
-    fn handle_pocketing(&mut self) {
-        for pocket in &self.pockets {
-            for ball in &mut self.balls {
-                if ball.active && pocket.contains_ball(ball.position.x, BALL_RADIUS) {
-                    ball.active = false; // Mark ball as potted
-                    ball.linear_velocity = Vec2::ZERO; // Stop poyted balls movement
-                    ball.angular_velocity = 0.0; // Stop  spin
-                    ball.position.y = TABLE_Y_POS + TABLE_THICKNESS + pocket.depth + BALL_RADIUS * 5.0;
-                }
-            }
+    pub fn reset(&mut self) {
+        self.elapsed_time = 0.0;
+        self.reference = None;
+        self.energy_log = EnergyLog::default();
+        self.trajectory = Trajectory::default();
+        self.history = History::default();
+        for ball in &mut self.balls {
+            ball.reset();
         }
     }
 
-    pub fn reset(&mut self) {
-        self.game_phase = GamePhase::Initial;
-        // Reset Q-ball
+    pub fn shoot_q_ball(&mut self, strike: &CueStrike) {
         if let Some(q_ball) = self.balls.iter_mut().find(|b| b.is_q_ball) {
-            q_ball.position = self.initial_q_ball_pos;
-            q_ball.linear_velocity = Vec2::ZERO;
-            q_ball.angular_velocity = 0.0;
-            q_ball.angle = 0.0;
-            q_ball.is_slipping = false;
-            q_ball.active = true;
+            if self.use_cue {
+                q_ball.linear_velocity.x = strike.linear_velocity(&self.params);
+                // An elevated cue drives the ball into the slate, which throws it back up
+                q_ball.linear_velocity.y = strike.vertical_velocity(&self.params);
+                q_ball.bounce_off_slate(&self.params);
+
+                if q_ball.has_angular_momentum {
+                    // Spin from where the tip hit, 0.0 for a centre hit
+                    q_ball.angular_velocity = strike.angular_velocity(&self.params); 
+                }
+            }
+            self.elapsed_time = 0.0;
+            self.reference = Some(ReferenceComparison::new(q_ball, &self.params));
         }
 
-        // Reset purple balls
-        if let Some(other_ball) = self.balls.iter_mut().find(|b| !b.is_q_ball) {
-            other_ball.position = self.initial_colored_ball_pos;
-            other_ball.linear_velocity = Vec2::ZERO;
-            other_ball.angular_velocity = 0.0;
-            other_ball.angle = 0.0;
-            other_ball.is_slipping = false;
-            other_ball.active = true;
-        }
+        self.energy_log = EnergyLog::default();
+        self.energy_log.record(0.0, &self.balls, &self.params);
+        self.trajectory = Trajectory::default();
+        self.trajectory.record(0.0, &self.balls);
+        self.history = History::default();
+        self.history.record(0.0, &self.balls);
     }
 
-    pub fn shoot_q_ball(&mut self) {
-        if let Some(q_ball) = self.balls.iter_mut().find(|b| b.is_q_ball) {
-            q_ball.linear_velocity.x = CUE_BALL_INITIAL_VELOCITY_MAGNITUDE;
+    // In pixels, the rails either side of the table share what's left of the view
+    pub fn table_draw_offset_x(&self) -> f32 {
+        (self.screen_view_width - px(self.playable_table_width)) / 2.0
+    }
 
-            if q_ball.has_angular_momentum {
-                // Initial angular velocity to 0.0 to gain spin
-                q_ball.angular_velocity = 0.0; 
-            }
-            self.game_phase = GamePhase::Running;
+    // Maps the world's view onto its grid cell, everything the world draws goes through this
+    pub fn camera(&self) -> Camera2D {
+        let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, self.screen_view_width, self.view_height));
+        camera.zoom.y = -camera.zoom.y; // from_display_rect comes out upside down on the screen
+        // Viewports are measured from the bottom of the window
+        camera.viewport = Some((
+            self.viewport.x as i32,
+            (screen_height() - self.viewport.y - self.viewport.h) as i32,
+            self.viewport.w as i32,
+            self.viewport.h as i32,
+        ));
+        camera
+    }
+
+    pub fn draw_energy(&self) {
+        self.energy_log.draw(self.screen_view_width);
+    }
+
+    pub fn draw_reference(&self) {
+        if let Some(reference) = &self.reference {
+            reference.draw(self.screen_view_width, self.table_draw_offset_x());
         }
     }
 
-    pub fn draw(&self) {
-        let table_draw_offset_x = self.screen_x_offset + (self.screen_view_width - self.playable_table_width) / 2.0;
+    // The balls as they were at `time` in this run, or as they are now
+    pub fn balls_at(&self, time: Option<f32>) -> &[Ball] {
+        match time.and_then(|t| self.history.at(t)) {
+            Some(snapshot) => &snapshot.balls,
+            None => &self.balls,
+        }
+    }
+
+    pub fn draw(&self, time: Option<f32>) {
+        let table_draw_offset_x = self.table_draw_offset_x();
+        let balls = self.balls_at(time);
+
+        let label = format!("{} ({})", self.label, self.game_phase.name());
+        let label_width = measure_text(&label, None, 20, 1.0).width;
+        draw_text(&label, (self.screen_view_width - label_width) / 2.0, 90.0, 20.0, BLACK);
+
+        let table_y = px(TABLE_Y_POS);
+        let table_width = px(self.playable_table_width);
+        let table_thickness = px(TABLE_THICKNESS);
 
         // Draw the pool table as a rectangle
         draw_rectangle(
             table_draw_offset_x,
-            TABLE_Y_POS,
-            self.playable_table_width,
-            TABLE_THICKNESS,
+            table_y,
+            table_width,
+            table_thickness,
             DARKGREEN, // Green
         );
 
         // Draw brown part
         draw_line(
-            table_draw_offset_x, TABLE_Y_POS,
-            table_draw_offset_x + self.playable_table_width, TABLE_Y_POS,
+            table_draw_offset_x, table_y,
+            table_draw_offset_x + table_width, table_y,
             4.0, BROWN // brown
         );
 
-        draw_rectangle(self.screen_x_offset, TABLE_Y_POS, (self.screen_view_width - self.playable_table_width) / 2.0, TABLE_THICKNESS, BROWN); // Left rail
-        draw_rectangle(self.screen_x_offset + self.playable_table_width + (self.screen_view_width - self.playable_table_width) / 2.0, TABLE_Y_POS, (self.screen_view_width - self.playable_table_width) / 2.0, TABLE_THICKNESS, BROWN); // Right rail
+        draw_rectangle(0.0, table_y, table_draw_offset_x, table_thickness, BROWN); // Left rail
+        draw_rectangle(table_width + table_draw_offset_x, table_y, table_draw_offset_x, table_thickness, BROWN); // Right rail
 
         // Draw pockets
         for pocket in &self.pockets {
+            let left = table_draw_offset_x + px(pocket.left_edge());
             draw_rectangle(
-                table_draw_offset_x + pocket.x_position - pocket.width / 2.0,
-                TABLE_Y_POS,
-                pocket.width,
-                pocket.depth + TABLE_THICKNESS, 
+                left,
+                table_y,
+                px(pocket.width),
+                px(pocket.depth) + table_thickness, 
                 BLACK, // Black
             );
             draw_line(
-                table_draw_offset_x + pocket.x_position - pocket.width / 2.0, TABLE_Y_POS + 1.0,
-                table_draw_offset_x + pocket.x_position + pocket.width / 2.0, TABLE_Y_POS + 1.0,
+                left, table_y + 1.0,
+                left + px(pocket.width), table_y + 1.0,
                 2.0, BROWN
             );
         }
 
         // Draw balls
-        for ball in &self.balls {
-            ball.draw(table_draw_offset_x); 
+        for ball in balls {
+            ball.draw(table_draw_offset_x, self.params.ball_radius); 
         }
 
-        if let Some(q_ball) = self.balls.iter().find(|b| b.is_q_ball) {
+        let integrator_name = self.integrator.name();
+        let integrator_width = measure_text(integrator_name, None, 18, 1.0).width;
+        draw_text(integrator_name, self.screen_view_width - integrator_width - 10.0, 20.0, 18.0, DARKGRAY);
+
+        if let Some(q_ball) = balls.iter().find(|b| b.is_q_ball) {
             draw_text(
-                &format!("Linear V: {:.2} px/s", q_ball.linear_velocity.x),
-                self.screen_x_offset + 10.0, 
+                &format!("Linear V: {:.3} m/s", q_ball.linear_velocity.x),
+                10.0, 
                 20.0,
                 20.0,
                 BLACK,
@@ -394,14 +1101,14 @@ impl SidePoolWorld {
             if q_ball.has_angular_momentum {
                 draw_text(
                     &format!("Angular V: {:.2} rad/s", q_ball.angular_velocity),
-                    self.screen_x_offset + 10.0,
+                    10.0,
                     40.0,
                     20.0,
                     BLACK,
                 );
                 draw_text(
                     &format!("Slipping: {}", q_ball.is_slipping),
-                    self.screen_x_offset + 10.0,
+                    10.0,
                     60.0,
                     20.0,
                     BLACK,
@@ -413,69 +1120,1058 @@ impl SidePoolWorld {
 
 
 pub struct SidePoolGame {
-    world_with_am: SidePoolWorld,
-    world_without_am: SidePoolWorld,
-    game_phase_global: GamePhase, 
+    worlds: Vec<SidePoolWorld>,
+    show_analytic_reference: bool,
+    show_energy_plots: bool,
+    cue_strike: CueStrike,
+    params_panel: ParamsPanel,
+    status_message: String,
+    timestep: FixedTimestep,
+    compared_configs: Option<Vec<WorldConfig>>, // Worlds to go back to when the integrator comparison ends
+    max_deviation: Vec<f32>, // Furthest each world's cue ball has been from the RK4 one this run
+    save_trajectories: bool, // Write every world's trajectory to disk when a run ends
+    overlays: SpinOverlays,
+    scenario: Option<Scenario>, // Laid out in every world, kept when the worlds are rebuilt
+    scenario_name: String,
+    scenario_menu: ScenarioMenu,
+    timeline: Timeline,
+    controls: ControlBar,
+    screen_size: Vec2, // The worlds were laid out for this, they follow when a browser or phone resizes
 }
 
 impl SidePoolGame {
-    pub async fn new() -> Self {
-        let full_screen_width = screen_width();
-        let screen_width_for_each_world_view = full_screen_width / 2.0; //Split screen
-        let world_with_am = SidePoolWorld::new(0.0, true, screen_width_for_each_world_view); // Left half
-        let world_without_am = SidePoolWorld::new(screen_width_for_each_world_view, false, screen_width_for_each_world_view); // Right half
+    pub async fn new(configs: Vec<WorldConfig>) -> Self {
+        let mut game = Self {
+            worlds: Vec::new(),
+            show_analytic_reference: false,
+            show_energy_plots: false,
+            cue_strike: CueStrike::default(),
+            params_panel: ParamsPanel::new(),
+            status_message: String::new(),
+            timestep: FixedTimestep::new(),
+            compared_configs: None,
+            max_deviation: Vec::new(),
+            save_trajectories: false, // R turns it on, it writes files after every run
+            overlays: SpinOverlays::new(),
+            scenario: None,
+            scenario_name: String::new(),
+            scenario_menu: ScenarioMenu::new(),
+            timeline: Timeline::new(),
+            controls: ControlBar::new(),
+            screen_size: Vec2::ZERO,
+        };
+        game.layout_worlds(configs);
+        game
+    }
 
-        Self {
-            world_with_am,
-            world_without_am,
-            game_phase_global: GamePhase::Initial,
+    fn world_area() -> Rect {
+        Rect::new(0.0, 0.0, screen_width(), screen_height() - HUD_HEIGHT)
+    }
+
+    // Builds a world per config in a grid cell above the HUD
+    fn layout_worlds(&mut self, configs: Vec<WorldConfig>) {
+        self.screen_size = Vec2::new(screen_width(), screen_height());
+        let cells = grid_cells(configs.len(), Self::world_area());
+        self.worlds = configs.iter().zip(cells).map(|(config, cell)| SidePoolWorld::new(config, cell)).collect();
+        if let Some(scenario) = &self.scenario {
+            for world in &mut self.worlds {
+                world.apply_scenario(scenario);
+            }
         }
+        self.params_panel.selected_world = self.params_panel.selected_world.min(self.worlds.len() - 1);
+        self.max_deviation = vec![0.0; self.worlds.len()];
     }
 
-    pub fn update(&mut self, dt: f32) {
-        if is_key_pressed(KeyCode::Space) {
-            // Spacebar resets
-            if matches!(self.game_phase_global, GamePhase::Initial | GamePhase::Running) {
-                self.world_with_am.reset();
-                self.world_without_am.reset();
-                self.game_phase_global = GamePhase::SetupShot;
-            } else if matches!(self.game_phase_global, GamePhase::SetupShot) {
-                // Spacebar shoots
-                self.world_with_am.shoot_q_ball();
-                self.world_without_am.shoot_q_ball();
-                self.game_phase_global = GamePhase::Running;
+    // Every world gets the same Space presses, so they only differ in when they settle
+    fn phase(&self) -> GamePhase {
+        if self.worlds.iter().any(|w| w.game_phase == GamePhase::Running) {
+            GamePhase::Running
+        } else {
+            self.worlds[0].game_phase
+        }
+    }
+
+    // Swaps the worlds for one copy of the selected world per integrator, and back again
+    fn toggle_integrator_comparison(&mut self) {
+        if let Some(configs) = self.compared_configs.take() {
+            self.layout_worlds(configs);
+            return;
+        }
+
+        let configs: Vec<WorldConfig> = self.worlds.iter().map(|w| w.config()).collect();
+        let base = configs[self.params_panel.selected_world].clone();
+        let comparison = Integrator::ALL
+            .map(|integrator| WorldConfig { label: integrator.name().to_string(), integrator, ..base.clone() })
+            .to_vec();
+        self.compared_configs = Some(configs);
+        self.layout_worlds(comparison);
+    }
+
+    fn track_deviation(&mut self) {
+        let q_ball_x = |world: &SidePoolWorld| world.balls.iter().find(|b| b.is_q_ball).map(|b| b.position.x);
+        let Some(reference_x) = self.worlds.iter()
+            .find(|w| w.integrator == Integrator::Rk4)
+            .and_then(q_ball_x) else { return; };
+
+        for (world, max_deviation) in self.worlds.iter().zip(&mut self.max_deviation) {
+            if let Some(x) = q_ball_x(world) {
+                *max_deviation = max_deviation.max((x - reference_x).abs());
+            }
+        }
+    }
+
+    // Furthest any world has run, the end of the timeline
+    fn live_time(&self) -> f32 {
+        self.worlds.iter().map(|w| w.elapsed_time).fold(0.0, f32::max)
+    }
+
+    // Along the bottom of the HUD, right of the key hints
+    fn timeline_bar(&self) -> Rect {
+        Rect::new(screen_width() * 0.55, screen_height() - CONTROL_BAR_HEIGHT - 22.0, screen_width() * 0.45 - 10.0, 12.0)
+    }
+
+    fn handle_timeline_input(&mut self) {
+        let live_time = self.live_time();
+        let dt = self.timestep.dt();
+        if self.controls.pressed(Control::Pause) {
+            self.timeline.toggle_pause();
+        }
+        if self.controls.pressed(Control::SlowMotion) {
+            self.timeline.cycle_speed();
+        }
+        if self.controls.pressed(Control::StepBack) {
+            self.timeline.step_back(dt, live_time);
+        }
+        if self.controls.pressed(Control::StepForward) && self.timeline.step_forward(dt, live_time) && self.phase() == GamePhase::Running {
+            self.step_worlds(1);
+        }
+
+        let bar = self.timeline_bar();
+        if let Some(point) = pointer_down().filter(|p| bar.contains(*p)) {
+            self.timeline.scrub_to((point.x - bar.x) / bar.w, live_time);
+        }
+    }
+
+    // Same number of equal steps in every world, settled worlds just wait
+    fn step_worlds(&mut self, steps: usize) {
+        let dt = self.timestep.dt();
+        for _ in 0..steps {
+            for world in &mut self.worlds {
+                world.update(dt);
+            }
+            self.track_deviation();
+        }
+        if self.phase() != GamePhase::Running && self.save_trajectories {
+            self.export_trajectories();
+        }
+    }
+
+    // The scenario's physics replace every world's, after that they can be tuned as usual
+    pub fn load_scenario(&mut self, path: &Path) {
+        match Scenario::load(path) {
+            Ok(scenario) => {
+                let mut configs: Vec<WorldConfig> = self.worlds.iter().map(|w| w.config()).collect();
+                if let Some(params) = &scenario.params {
+                    for config in &mut configs {
+                        config.params = params.clone();
+                    }
+                }
+                self.scenario_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+                self.status_message = if scenario.description.is_empty() {
+                    format!("Scenario: {}", scenario.name)
+                } else {
+                    format!("Scenario: {} - {}", scenario.name, scenario.description)
+                };
+                self.scenario = Some(scenario);
+                self.layout_worlds(configs);
+            }
+            Err(err) => self.status_message = format!("Could not load {}: {}", path.display(), err),
+        }
+    }
+
+    // New worlds start as a copy of the selected one, ready to be tuned
+    fn add_world(&mut self) {
+        let mut configs: Vec<WorldConfig> = self.worlds.iter().map(|w| w.config()).collect();
+        let mut config = configs[self.params_panel.selected_world].clone();
+        config.label = format!("World {}", configs.len() + 1);
+        configs.push(config);
+        self.layout_worlds(configs);
+    }
+
+    fn remove_world(&mut self) {
+        if self.worlds.len() > 1 {
+            let mut configs: Vec<WorldConfig> = self.worlds.iter().map(|w| w.config()).collect();
+            configs.pop();
+            self.layout_worlds(configs);
+        }
+    }
+
+    fn control_bar_area() -> Rect {
+        Rect::new(0.0, screen_height() - CONTROL_BAR_HEIGHT, screen_width(), CONTROL_BAR_HEIGHT)
+    }
+
+    // The worlds keep running, they just move into the new grid
+    fn fit_to_screen(&mut self) {
+        let screen_size = Vec2::new(screen_width(), screen_height());
+        if screen_size == self.screen_size {
+            return;
+        }
+        self.screen_size = screen_size;
+        let cells = grid_cells(self.worlds.len(), Self::world_area());
+        for (world, cell) in self.worlds.iter_mut().zip(cells) {
+            world.set_viewport(cell);
+        }
+    }
+
+    pub fn update(&mut self, frame_dt: f32) {
+        self.fit_to_screen();
+        self.controls.update(Self::control_bar_area(), !self.params_panel.typing());
+        if self.controls.pressed(Control::Analytic) {
+            self.show_analytic_reference = !self.show_analytic_reference;
+        }
+        if self.controls.pressed(Control::Energy) {
+            self.show_energy_plots = !self.show_energy_plots;
+        }
+        if self.controls.pressed(Control::ExportEnergy) {
+            self.export_energy_logs();
+        }
+        if self.controls.pressed(Control::Parameters) {
+            self.params_panel.toggle();
+        }
+        if self.controls.pressed(Control::SaveTrajectories) {
+            self.save_trajectories = !self.save_trajectories;
+        }
+        self.overlays.handle_input(&self.controls);
+        self.handle_timeline_input();
+
+        if self.phase() != GamePhase::Running {
+            self.cue_strike.handle_input(&self.controls);
+            let comparing = self.compared_configs.is_some();
+            if self.controls.pressed(Control::AddWorld) && !comparing {
+                self.add_world();
+            }
+            if self.controls.pressed(Control::RemoveWorld) && !comparing {
+                self.remove_world();
+            }
+            if self.controls.pressed(Control::Integrator) && !comparing {
+                for world in &mut self.worlds {
+                    world.integrator = world.integrator.next();
+                }
+            }
+            if self.controls.pressed(Control::Timestep) {
+                self.timestep.cycle();
+            }
+            if self.controls.pressed(Control::CompareIntegrators) {
+                self.toggle_integrator_comparison();
+            }
+            if self.controls.pressed(Control::Scenarios) {
+                self.scenario_menu.toggle();
+            }
+            // A lecture's demos, numbered in the scenarios folder, go forwards and back with the brackets
+            let step = if self.controls.pressed(Control::NextScenario) {
+                1
+            } else if self.controls.pressed(Control::PreviousScenario) {
+                -1
+            } else {
+                0
+            };
+            let mut chosen = if step != 0 { self.scenario_menu.neighbour(&self.scenario_name, step) } else { None };
+            if self.scenario_menu.visible {
+                chosen = chosen.or(self.scenario_menu.ui(&self.scenario_name));
+            }
+            if let Some(name) = chosen.filter(|_| !comparing) {
+                self.load_scenario(&scenario_path(&name));
+            }
+            if self.params_panel.visible {
+                self.edit_params();
             }
-            return; // Exit after press spacebar
         }
 
-        if matches!(self.game_phase_global, GamePhase::Running) {
-            self.world_with_am.update(dt);
-            self.world_without_am.update(dt);
+        if self.controls.pressed(Control::Shoot) {
+            // Racks, shoots or resets depending on the phase, the same strike in every world
+            for world in &mut self.worlds {
+                world.handle(PhaseEvent::SpacePressed, &self.cue_strike);
+            }
+            if self.phase() == GamePhase::Running {
+                self.max_deviation = vec![0.0; self.worlds.len()];
+            }
+            self.timeline.go_live();
+        }
+
+        // Paused or replaying the history, no physics runs. Slow motion feeds it less time per frame.
+        let physics_time = self.timeline.advance(frame_dt, self.live_time());
+        if self.phase() == GamePhase::Running {
+            let steps = self.timestep.steps(physics_time);
+            self.step_worlds(steps);
+        }
+    }
 
-            let all_stopped_am = self.world_with_am.balls.iter()
-                .filter(|b| b.active) // Only consider active balls
-                .all(|b| b.linear_velocity.length() < MIN_VELOCITY_THRESHOLD && b.angular_velocity.abs() < MIN_ANGULAR_VELOCITY_THRESHOLD);
+    // Parameters only change between runs so a shot never mixes two sets of physics
+    fn edit_params(&mut self) {
+        let selected = self.params_panel.selected_world;
+        let labels: Vec<&str> = self.worlds.iter().map(|w| w.label.as_str()).collect();
+        let mut params = self.worlds[selected].params.clone();
+
+        let action = self.params_panel.ui(&labels, &mut params);
+        match action {
+            PanelAction::ApplyToAll => {
+                for world in &mut self.worlds {
+                    world.set_params(params.clone());
+                }
+            }
+            PanelAction::None => {
+                // The panel may have switched worlds this frame, only write back to the one we read
+                let world = &mut self.worlds[selected];
+                if world.params != params {
+                    world.set_params(params);
+                }
+            }
+        }
+    }
 
-            let all_stopped_no_am = self.world_without_am.balls.iter()
-                .filter(|b| b.active) // Only consider active balls
-                .all(|b| b.linear_velocity.length() < MIN_VELOCITY_THRESHOLD);
+    fn export_energy_logs(&mut self) {
+        let mut saved = Vec::new();
+        for (i, world) in self.worlds.iter().enumerate() {
+            let file_name = format!("energy_{}_{}.csv", i + 1, file_name_part(&world.label));
+            match write_output(&file_name, world.energy_log.to_csv()) {
+                Ok(()) => saved.push(file_name),
+                Err(err) => {
+                    self.status_message = format!("Could not save {}: {}", file_name, err);
+                    return;
+                }
+            }
+        }
+        self.status_message = format!("Saved {} energy logs in {}/", saved.len(), OUTPUT_DIR);
+    }
 
-            if all_stopped_am && all_stopped_no_am {
-                self.game_phase_global = GamePhase::Initial;
+    // A CSV and a JSON file per world, overwritten by the next run
+    fn export_trajectories(&mut self) {
+        let mut saved = 0;
+        for (i, world) in self.worlds.iter().enumerate() {
+            let file_stem = format!("trajectory_{}_{}", i + 1, file_name_part(&world.label));
+            for (extension, contents) in [("csv", world.trajectory.to_csv()), ("json", world.trajectory.to_json())] {
+                let file_name = format!("{}.{}", file_stem, extension);
+                if let Err(err) = write_output(&file_name, contents) {
+                    self.status_message = format!("Could not save {}: {}", file_name, err);
+                    return;
+                }
+                saved += 1;
             }
         }
+        self.status_message = format!("Saved {} trajectory files in {}/", saved, OUTPUT_DIR);
+    }
+
+    fn draw_timeline(&self, y: f32) {
+        let live_time = self.live_time();
+        let view_time = self.timeline.view_time(live_time);
+        let play_state = if self.timeline.paused { "play" } else { "pause" };
+        draw_text(
+            &format!(
+                "K: {}  O: slow motion (x{})  ,/.: step  t {:.2} / {:.2} s",
+                play_state,
+                self.timeline.speed(),
+                view_time,
+                live_time
+            ),
+            10.0,
+            y,
+            18.0,
+            DARKGRAY,
+        );
+
+        let bar = self.timeline_bar();
+        let fraction = if live_time > 0.0 { view_time / live_time } else { 1.0 };
+        draw_rectangle(bar.x, bar.y, bar.w, bar.h, GRAY);
+        draw_rectangle(bar.x, bar.y, bar.w * fraction, bar.h, DARKGRAY);
+        let marker_x = bar.x + bar.w * fraction;
+        let marker_color = if self.timeline.scrub_time.is_some() { ORANGE } else { BLACK };
+        draw_line(marker_x, bar.y - 3.0, marker_x, bar.y + bar.h + 3.0, 3.0, marker_color);
     }
 
     pub fn draw(&self) {
         clear_background(LIGHTGRAY);
 
-        // Draw the split screen divider
-        draw_line(screen_width() / 2.0, 0.0, screen_width() / 2.0, screen_height(), 2.0, BLACK);
-        draw_text("With Angular Momentum", screen_width() / 4.0 - 80.0, 90.0, 20.0, BLACK);
-        draw_text("Without Angular Momentum", screen_width() * 3.0 / 4.0 - 100.0, 90.0, 20.0, BLACK);
+        for world in &self.worlds {
+            set_camera(&world.camera());
+            world.draw(self.timeline.scrub_time);
+            for ball in world.balls_at(self.timeline.scrub_time) {
+                self.overlays.draw(ball, world.table_draw_offset_x(), &world.params);
+            }
+
+            if world.game_phase == GamePhase::Aiming && world.use_cue {
+                self.cue_strike.draw(world);
+            }
+            if self.show_analytic_reference {
+                world.draw_reference();
+            }
+            if self.show_energy_plots {
+                world.draw_energy();
+            }
+        }
+        if self.compared_configs.is_some() {
+            for (world, max_deviation) in self.worlds.iter().zip(&self.max_deviation) {
+                set_camera(&world.camera());
+                let text = format!("Off RK4 by up to {:.3} mm", max_deviation * 1000.0);
+                let text_width = measure_text(&text, None, 18, 1.0).width;
+                draw_text(&text, world.screen_view_width - text_width - 10.0, 40.0, 18.0, DARKGRAY);
+            }
+        }
+        set_default_camera();
+
+        // Grid lines, the world being tuned is highlighted
+        for (i, world) in self.worlds.iter().enumerate() {
+            let cell = world.viewport;
+            let selected = self.params_panel.visible && i == self.params_panel.selected_world;
+            let (thickness, color) = if selected { (3.0, ORANGE) } else { (2.0, BLACK) };
+            draw_rectangle_lines(cell.x, cell.y, cell.w, cell.h, thickness, color);
+        }
+
+        let hud_top = screen_height() - HUD_HEIGHT;
+        draw_text(self.phase().prompt(), 10.0, hud_top + 18.0, 22.0, BLACK);
+        draw_text(&self.status_message, screen_width() * 0.6, hud_top + 18.0, 18.0, DARKGRAY);
+
+        let cue_params = &self.worlds[self.params_panel.selected_world].params;
+        self.cue_strike.draw_hud(10.0, hud_top + 36.0, cue_params);
+
+        let saving_state = if self.save_trajectories { "on" } else { "off" };
+        draw_text(
+            &format!(
+                "A: analytic  E: energy  C: export CSV  P: parameters  +/-: add/remove world  R: save trajectories ({})",
+                saving_state
+            ),
+            10.0,
+            hud_top + 74.0,
+            18.0,
+            DARKGRAY,
+        );
+        let comparison_state = if self.compared_configs.is_some() { "on" } else { "off" };
+        draw_text(
+            &format!(
+                "I: integrator  T: timestep ({:.1} ms)  M: compare integrators ({})  L: scenarios  [/]: previous/next scenario",
+                self.timestep.dt() * 1000.0,
+                comparison_state
+            ),
+            10.0,
+            hud_top + 92.0,
+            18.0,
+            DARKGRAY,
+        );
+        draw_text(&self.overlays.legend(), 10.0, hud_top + 110.0, 18.0, DARKGRAY);
+        self.draw_timeline(hud_top + 130.0);
+
+        let phase = self.phase();
+        let paused = self.timeline.paused;
+        self.controls.draw(|control| match control {
+            Control::Shoot => phase.action().to_string(),
+            Control::Pause if paused => String::from("Play"),
+            Control::SaveTrajectories if self.save_trajectories => String::from("Recording"),
+            _ => control.label().to_string(),
+        });
+    }
+}
+
+// Lower case with underscores, safe to put in a file name
+fn file_name_part(label: &str) -> String {
+    label.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+fn write_output(file_name: &str, contents: String) -> std::io::Result<()> {
+    std::fs::create_dir_all(OUTPUT_DIR)?;
+    std::fs::write(Path::new(OUTPUT_DIR).join(file_name), contents)
+}
+
+// The other modules' tests share these set-ups
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::params::BALL_RADIUS_RANGE;
+
+    pub(crate) const PARAMS: PhysicsParams = PhysicsParams::DEFAULT;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // A table long enough that the cue ball never reaches a cushion
+    pub(crate) fn lone_cue_ball_world(has_angular_momentum: bool) -> SidePoolWorld {
+        let mut world = SidePoolWorld::headless(1000.0, has_angular_momentum);
+        world.balls.retain(|b| b.is_q_ball);
+        world
+    }
+
+    fn run_until_settled(world: &mut SidePoolWorld, max_time: f32) -> f32 {
+        let mut elapsed = 0.0;
+        while !world.is_settled() && elapsed < max_time {
+            world.step(DT);
+            elapsed += DT;
+        }
+        elapsed
+    }
+
+    #[test]
+    fn sliding_ball_stops_at_kinetic_friction_distance() {
+        let mut world = lone_cue_ball_world(false);
+        let start_x = world.balls[0].position.x;
+        world.shoot_q_ball(&CueStrike::default());
+        run_until_settled(&mut world, 60.0);
+
+        let v0 = PARAMS.cue_ball_initial_velocity;
+        let expected = v0 * v0 / (2.0 * PARAMS.kinetic_friction_coeff * GRAVITY);
+        let travelled = world.balls[0].position.x - start_x;
+        assert!(world.is_settled());
+        // The slide only lasts half a second, so allow for the integrator being a step out
+        assert!((travelled - expected).abs() < v0 * DT, "travelled {travelled}, expected {expected}");
+    }
+
+    #[test]
+    fn spinning_ball_starts_rolling_at_analytic_time() {
+        let mut world = lone_cue_ball_world(true);
+        world.shoot_q_ball(&CueStrike::default());
+
+        let mut elapsed = 0.0;
+        world.step(DT);
+        while world.balls[0].is_slipping && elapsed < 10.0 {
+            world.step(DT);
+            elapsed += DT;
+        }
+
+        let v0 = PARAMS.cue_ball_initial_velocity;
+        let expected_time = 2.0 * v0 / (7.0 * PARAMS.kinetic_friction_coeff * GRAVITY);
+        let ball = &world.balls[0];
+        assert!((elapsed - expected_time).abs() < DT, "rolled after {elapsed}s, expected {expected_time}s");
+        assert!((ball.linear_velocity.x - v0 * 5.0 / 7.0).abs() < 0.1);
+        assert_eq!(ball.relative_velocity_at_contact_x(&PARAMS), 0.0);
+    }
+
+    #[test]
+    fn rolling_ball_decelerates_at_five_sevenths_of_rolling_resistance() {
+        let mut world = lone_cue_ball_world(true);
+        let ball = &mut world.balls[0];
+        ball.linear_velocity.x = 1.0;
+        ball.angular_velocity = 1.0 / PARAMS.ball_radius;
+
+        let start_x = ball.position.x;
+        run_until_settled(&mut world, 600.0);
+
+        let deceleration = 5.0 / 7.0 * PARAMS.rolling_friction_coeff * GRAVITY;
+        let expected = 1.0 * 1.0 / (2.0 * deceleration);
+        let travelled = world.balls[0].position.x - start_x;
+        assert!((travelled - expected).abs() < expected * 0.01, "travelled {travelled}, expected {expected}");
+        assert!(!world.balls[0].is_slipping);
+    }
+
+    #[test]
+    fn slide_to_roll_transition_is_independent_of_step_size() {
+        let final_velocity = |dt: f32| {
+            let mut world = lone_cue_ball_world(true);
+            world.shoot_q_ball(&CueStrike::default());
+            for _ in 0..(3.0 / dt) as usize {
+                world.step(dt);
+            }
+            world.balls[0].linear_velocity.x
+        };
+
+        assert!((final_velocity(1.0 / 30.0) - final_velocity(1.0 / 240.0)).abs() < 0.05);
+    }
+
+    #[test]
+    fn rolling_ball_travels_further_than_sliding_ball() {
+        let mut sliding = lone_cue_ball_world(false);
+        let mut rolling = lone_cue_ball_world(true);
+        sliding.shoot_q_ball(&CueStrike::default());
+        rolling.shoot_q_ball(&CueStrike::default());
+        run_until_settled(&mut sliding, 60.0);
+        run_until_settled(&mut rolling, 600.0);
+
+        assert!(rolling.is_settled());
+        assert!(rolling.balls[0].position.x > sliding.balls[0].position.x);
+    }
+
+    #[test]
+    fn medium_pace_shot_stops_in_a_realistic_distance() {
+        let mut world = lone_cue_ball_world(true);
+        let start_x = world.balls[0].position.x;
+        world.shoot_q_ball(&CueStrike::default());
+        world.step(DT);
+        while world.balls[0].is_slipping {
+            world.step(DT);
+        }
+        let slide = world.balls[0].position.x - start_x;
+        run_until_settled(&mut world, 60.0);
+        let travelled = world.balls[0].position.x - start_x;
+
+        // A stun shot at 1 m/s on standard cloth skids a few ball widths, then rolls
+        // one to two lengths of a 9 ft table (2.54 m) before it stops
+        assert!(world.is_settled());
+        assert!(slide > 0.05 && slide < 0.15, "slid {slide} m");
+        assert!(travelled > 2.5 && travelled < 5.0, "travelled {travelled} m");
+    }
+
+    #[test]
+    fn ball_reaching_pocket_is_potted() {
+        let mut world = SidePoolWorld::headless(1.2, true);
+        world.balls.retain(|b| !b.is_q_ball);
+        world.balls[0].linear_velocity.x = 0.5;
+        run_until_settled(&mut world, 10.0);
+
+        assert!(!world.balls[0].active);
+        assert_eq!(world.balls[0].pocket_index, Some(1));
+        assert_eq!(world.balls[0].linear_velocity, Vec2::ZERO);
+        // It drops all the way to the bottom of the pocket
+        assert_eq!(world.balls[0].position.y, world.pockets[1].bottom_y(PARAMS.ball_radius));
+    }
+
+    #[test]
+    fn biggest_ball_still_fits_the_pockets() {
+        let ball_radius = BALL_RADIUS_RANGE.end;
+        let mut world = SidePoolWorld::headless(1.2, true);
+        world.balls.retain(|b| !b.is_q_ball);
+        world.set_params(PhysicsParams { ball_radius, ..PhysicsParams::default() });
+        assert!(world.pockets.iter().all(|p| p.width > 2.0 * ball_radius));
+
+        world.balls[0].linear_velocity.x = 0.5;
+        run_until_settled(&mut world, 10.0);
+        assert!(world.is_settled());
+        assert_eq!(world.balls[0].pocket_index, Some(1));
+    }
+
+    // A ball heading for the right hand pocket at `velocity`, with spin as a fraction of rolling spin.
+    // It starts close enough that a stun shot is still sliding when it reaches the jaw.
+    fn ball_at_pocket(velocity: f32, spin: f32) -> Ball {
+        let mut world = SidePoolWorld::with_balls(0.9, vec![ball_at(0.8, velocity, spin * velocity / PARAMS.ball_radius)]);
+        run_until_settled(&mut world, 20.0);
+        world.balls.remove(0)
+    }
+
+    #[test]
+    fn fast_ball_skips_over_the_far_jaw() {
+        let ball = ball_at_pocket(2.0, 1.0);
+        assert_eq!(ball.pocket_index, None);
+        assert!(ball.position.x > 0.9 + PARAMS.ball_radius); // Past the far jaw and off the end of the table
+    }
+
+    #[test]
+    fn topspin_climbs_out_of_a_pocket_that_takes_a_stun_shot() {
+        let stun = ball_at_pocket(0.9, 0.0);
+        assert_eq!(stun.pocket_index, Some(1));
+
+        // Same speed, but rolling: the spin grips the far jaw and throws the ball back out
+        let follow = ball_at_pocket(0.9, 1.0);
+        assert_ne!(follow.pocket_index, Some(1));
+    }
+
+    #[test]
+    fn ball_hanging_over_the_edge_falls_in() {
+        let mut world = SidePoolWorld::with_balls(0.9, vec![ball_at(0.89, 0.0, 0.0)]);
+        world.step(DT);
+        assert!(world.balls[0].airborne);
+        run_until_settled(&mut world, 5.0);
+        assert_eq!(world.balls[0].pocket_index, Some(1));
+    }
+
+    #[test]
+    fn slow_ball_stops_short_of_pocket() {
+        let mut world = SidePoolWorld::headless(1.2, false);
+        world.balls.retain(|b| !b.is_q_ball);
+        world.balls[0].linear_velocity.x = 0.05;
+        run_until_settled(&mut world, 10.0);
+
+        assert!(world.balls[0].active);
+        assert!(world.balls[0].position.x < world.playable_table_width);
+    }
+
+    #[test]
+    fn cue_ball_transfers_most_of_its_speed_to_object_ball() {
+        let mut world = SidePoolWorld::headless(1.5, false);
+        // Close enough that the sliding cue ball still reaches it
+        world.balls[1].position.x = world.balls[0].position.x + 0.15;
+        world.shoot_q_ball(&CueStrike::default());
+        let mut elapsed = 0.0;
+        while world.balls[1].linear_velocity.x == 0.0 && elapsed < 10.0 {
+            world.step(DT);
+            elapsed += DT;
+        }
+
+        assert!(world.balls[1].linear_velocity.x > world.balls[0].linear_velocity.x);
+        assert!(world.balls[0].linear_velocity.x >= 0.0);
+    }
+
+    // Cue ball a little way off a row of three touching balls
+    fn cradle_world() -> SidePoolWorld {
+        let y = TABLE_Y_POS - PARAMS.ball_radius;
+        let mut balls = vec![Ball::new(Vec2::new(0.3, y), WHITE, true, false)];
+        for (i, color) in [RED, YELLOW, BLUE].into_iter().enumerate() {
+            let x = 0.6 + i as f32 * PARAMS.ball_radius * 2.0;
+            balls.push(Ball::new(Vec2::new(x, y), color, false, false));
+        }
+        SidePoolWorld::with_balls(3.0, balls)
+    }
+
+    #[test]
+    fn hit_travels_down_a_chain_of_touching_balls() {
+        let mut world = cradle_world();
+        world.shoot_q_ball(&CueStrike::default());
+        let mut elapsed = 0.0;
+        while world.balls[3].linear_velocity.x == 0.0 && elapsed < 5.0 {
+            world.step(DT);
+            elapsed += DT;
+        }
+
+        let last = &world.balls[3];
+        assert!(last.linear_velocity.x > 0.0);
+        for i in 0..3 {
+            assert!(world.balls[i].linear_velocity.x < last.linear_velocity.x);
+        }
+    }
+
+    #[test]
+    fn balls_never_pass_through_each_other() {
+        let mut world = cradle_world();
+        world.shoot_q_ball(&CueStrike { force_factor: 2.0, ..CueStrike::default() });
+        for _ in 0..600 {
+            world.step(DT);
+            let active: Vec<f32> = world.balls.iter().filter(|b| b.active).map(|b| b.position.x).collect();
+            for pair in active.windows(2) {
+                assert!(pair[1] - pair[0] > PARAMS.ball_radius); // Small overlaps between steps are fine, swapping order is not
+            }
+        }
+    }
+
+    #[test]
+    fn ball_moving_left_hits_ball_on_its_left() {
+        let mut world = cradle_world();
+        world.balls.retain(|b| !b.is_q_ball);
+        world.balls[2].linear_velocity.x = -0.5;
+        world.balls[2].position.x += 0.05;
+        run_until_settled(&mut world, 20.0);
+
+        assert!(world.balls[0].position.x < world.balls[0].initial_position.x);
+    }
+
+    #[test]
+    fn reset_puts_every_ball_back() {
+        let mut world = cradle_world();
+        world.shoot_q_ball(&CueStrike::default());
+        run_until_settled(&mut world, 20.0);
+        world.reset();
+
+        for ball in &world.balls {
+            assert_eq!(ball.position, ball.initial_position);
+            assert_eq!(ball.linear_velocity, Vec2::ZERO);
+            assert!(ball.active);
+        }
+    }
+
+    fn ball_at(x: f32, velocity: f32, angular_velocity: f32) -> Ball {
+        let mut ball = Ball::new(Vec2::new(x, TABLE_Y_POS - PARAMS.ball_radius), WHITE, false, true);
+        ball.linear_velocity.x = velocity;
+        ball.angular_velocity = angular_velocity;
+        ball
+    }
+
+    #[test]
+    fn collision_conserves_momentum_and_loses_energy_by_restitution() {
+        let mut left = ball_at(0.3, 1.2, 0.0);
+        let mut right = ball_at(0.3 + PARAMS.ball_radius * 2.0, -0.3, 0.0);
+        let momentum = |a: &Ball, b: &Ball| PARAMS.ball_mass * (a.linear_velocity.x + b.linear_velocity.x);
+        let momentum_before = momentum(&left, &right);
+
+        resolve_collision(&mut left, &mut right, &PARAMS);
+
+        assert!((momentum(&left, &right) - momentum_before).abs() < 1e-5);
+        let separating_velocity = right.linear_velocity.x - left.linear_velocity.x;
+        assert!((separating_velocity - PARAMS.ball_restitution_coeff * 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn head_on_stun_shot_stops_the_cue_ball() {
+        let mut cue = ball_at(0.3, 1.0, 0.0);
+        let mut object = ball_at(0.3 + PARAMS.ball_radius * 2.0, 0.0, 0.0);
+        resolve_collision(&mut cue, &mut object, &PARAMS);
+
+        assert!(cue.linear_velocity.x.abs() < 1.0 - PARAMS.ball_restitution_coeff);
+        assert!(object.linear_velocity.x > 0.95);
+        assert_eq!(object.angular_velocity, 0.0);
+    }
+
+    #[test]
+    fn contact_friction_passes_opposite_spin_to_object_ball() {
+        let mut cue = ball_at(0.3, 1.0, 10.0);
+        let mut object = ball_at(0.3 + PARAMS.ball_radius * 2.0, 0.0, 0.0);
+        resolve_collision(&mut cue, &mut object, &PARAMS);
+
+        // Topspin on the cue ball rubs backspin onto the object ball, limited by ball friction
+        assert!(object.angular_velocity < 0.0);
+        assert!(cue.angular_velocity < 10.0 && cue.angular_velocity > 0.0);
+        let max_change = PARAMS.ball_friction_coeff * (1.0 + PARAMS.ball_restitution_coeff) * PARAMS.ball_mass / 2.0 * 1.0 * PARAMS.ball_radius / PARAMS.moment_of_inertia();
+        assert!((10.0 - cue.angular_velocity) <= max_change + 1e-3);
+    }
+
+    #[test]
+    fn friction_opposes_slip_until_the_ball_rolls() {
+        let weight = PARAMS.ball_mass * GRAVITY;
+        // Backspin: the contact point slides forwards, so friction pushes back
+        let mut ball = ball_at(0.15, 1.0, -5.0);
+        assert!(ball.relative_velocity_at_contact_x(&PARAMS) > 0.0);
+        assert!((ball.friction_force_x(&PARAMS) + PARAMS.kinetic_friction_coeff * weight).abs() < 1e-4);
+
+        // Rolling: only rolling resistance is left
+        ball.angular_velocity = ball.linear_velocity.x / PARAMS.ball_radius;
+        let rolling_force = ball.friction_force_x(&PARAMS);
+        assert!(rolling_force < 0.0);
+        assert!(rolling_force.abs() < PARAMS.rolling_friction_coeff * weight);
+
+        // And it matches how the ball actually slows
+        let before = ball.linear_velocity.x;
+        ball.update(DT, &PARAMS, Integrator::Rk4);
+        let deceleration = (ball.linear_velocity.x - before) / DT;
+        assert!((deceleration - rolling_force / PARAMS.ball_mass).abs() < 1e-2);
+
+        ball.linear_velocity = Vec2::ZERO;
+        ball.angular_velocity = 0.0;
+        assert_eq!(ball.friction_force_x(&PARAMS), 0.0);
+    }
+
+    // One short step of a ball just touching the right cushion, with the pockets closed
+    fn cushion_rebound(params: PhysicsParams, velocity: f32, angular_velocity: f32) -> SidePoolWorld {
+        let ball = ball_at(0.9 - PARAMS.ball_radius * 0.85, velocity, angular_velocity);
+        let mut world = SidePoolWorld::with_balls(0.9, vec![ball]);
+        for pocket in &mut world.pockets {
+            pocket.width = 0.0;
+        }
+        world.set_params(params);
+        world.shoot_q_ball(&CueStrike::default()); // Starts the energy log, there's no cue ball to hit
+        world.step(1.0 / 1000.0);
+        world
+    }
+
+    #[test]
+    fn simple_cushion_model_is_kept_for_comparison() {
+        let params = PhysicsParams { cushion_model: CushionModel::Simple, ..PARAMS };
+        let velocity = 0.6;
+        let world = cushion_rebound(params, velocity, velocity / PARAMS.ball_radius);
+        let ball = &world.balls[0];
+        let restitution = PARAMS.wall_restitution_coeff;
+        assert!((ball.linear_velocity.x + restitution * 0.5_f32.sqrt() * velocity).abs() < 0.01);
+        assert!((ball.angular_velocity + restitution * velocity / PARAMS.ball_radius).abs() < 0.1);
+        assert_eq!(ball.linear_velocity.y, 0.0);
+    }
+
+    #[test]
+    fn frictionless_nose_level_with_the_centre_bounces_straight_back() {
+        let params = PhysicsParams { cushion_nose_height: 1.0, cushion_friction_coeff: 0.0, ..PARAMS };
+        let velocity = 0.6;
+        let spin = velocity / PARAMS.ball_radius;
+        let world = cushion_rebound(params, velocity, spin);
+        let ball = &world.balls[0];
+        assert!((ball.linear_velocity.x + PARAMS.wall_restitution_coeff * velocity).abs() < 0.01);
+        assert_eq!(ball.linear_velocity.y, 0.0);
+        assert!(!ball.airborne);
+        // The impulse goes through the centre, so the spin is untouched
+        assert!((ball.angular_velocity - spin).abs() < 0.1);
+    }
+
+    #[test]
+    fn regulation_nose_drives_the_ball_into_the_slate_and_takes_off_topspin() {
+        let velocity = 0.9;
+        let spin = velocity / PARAMS.ball_radius;
+        let world = cushion_rebound(PARAMS, velocity, spin);
+        let ball = &world.balls[0];
+        assert!(ball.linear_velocity.x < 0.0);
+        assert!(ball.angular_velocity > 0.0 && ball.angular_velocity < spin * 0.5);
+        // Some of the bounce went down into the slate and was lost there
+        assert!(world.energy_log.bounce_loss > 0.0);
+        assert!(ball.linear_velocity.x.abs() < PARAMS.wall_restitution_coeff * velocity);
+    }
+
+    #[test]
+    fn ball_above_the_nose_clears_the_cushion() {
+        let mut ball = ball_at(0.87, 0.6, 0.0);
+        ball.position.y -= PARAMS.ball_radius * 2.0;
+        ball.airborne = true;
+        let mut world = SidePoolWorld::with_balls(0.9, vec![ball]);
+        for pocket in &mut world.pockets {
+            pocket.width = 0.0;
+        }
+        world.step(1.0 / 1000.0);
+        assert!(world.balls[0].linear_velocity.x > 0.0);
+    }
+
+    #[test]
+    fn grid_has_a_cell_per_world_inside_the_area() {
+        let area = Rect::new(0.0, 0.0, 800.0, 540.0);
+        for count in 1..=9 {
+            let cells = grid_cells(count, area);
+            assert_eq!(cells.len(), count);
+            for cell in &cells {
+                assert!(cell.x >= area.x && cell.right() <= area.right() + 1e-3);
+                assert!(cell.y >= area.y && cell.bottom() <= area.bottom() + 1e-3);
+            }
+            // Equal cells inside the area only fit without overlapping if their total area fits too
+            let total_area: f32 = cells.iter().map(|c| c.w * c.h).sum();
+            assert!(total_area <= area.w * area.h + 1e-2);
+        }
+    }
+
+    #[test]
+    fn two_worlds_sit_side_by_side_and_three_make_a_square() {
+        let area = Rect::new(0.0, 0.0, 800.0, 540.0);
+        let two = grid_cells(2, area);
+        assert_eq!(two[1], Rect::new(400.0, 0.0, 400.0, 540.0));
 
+        let three = grid_cells(3, area);
+        assert_eq!(three[2], Rect::new(0.0, 270.0, 400.0, 270.0));
+    }
+
+    #[test]
+    fn each_world_keeps_its_own_parameters() {
+        let slow_cloth = PhysicsParams { kinetic_friction_coeff: 0.4, ..PhysicsParams::default() };
+        let configs = [
+            WorldConfig::new("Default", true, PhysicsParams::default()),
+            WorldConfig::new("Slow cloth", true, slow_cloth.clone()),
+        ];
+        let cells = grid_cells(configs.len(), Rect::new(0.0, 0.0, 800.0, 540.0));
+        let worlds: Vec<SidePoolWorld> = configs.iter().zip(cells).map(|(c, cell)| SidePoolWorld::new(c, cell)).collect();
 
-        self.world_with_am.draw();
-        self.world_without_am.draw();
+        assert_eq!(worlds[1].params, slow_cloth);
+        assert_eq!(worlds[1].config().label, "Slow cloth");
+        assert_eq!(worlds[0].screen_view_width, 400.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn resizing_the_window_refits_the_view_but_keeps_the_table() {
+        let config = WorldConfig::new("Default", true, PhysicsParams::default());
+        let mut world = SidePoolWorld::new(&config, Rect::new(0.0, 0.0, 800.0, 540.0));
+        let table_width = world.playable_table_width;
+
+        // Turned on its side, the view gets taller rather than cutting the table off
+        let portrait = Rect::new(0.0, 0.0, 400.0, 700.0);
+        world.set_viewport(portrait);
+        assert_eq!(world.viewport, portrait);
+        assert_eq!(world.playable_table_width, table_width);
+        assert!((world.screen_view_width / world.view_height - 400.0 / 700.0).abs() < 1e-4);
+        assert!(world.screen_view_width * WORLD_TABLE_PLAYABLE_WIDTH_FACTOR >= px(table_width) - 1e-3);
+
+        world.set_viewport(Rect::new(0.0, 0.0, 800.0, 540.0));
+        assert!((world.screen_view_width - 800.0).abs() < 1e-3);
+        assert!((world.view_height - WORLD_VIEW_HEIGHT).abs() < 1e-3);
+    }
+
+    fn stopping_position(integrator: Integrator, dt: f32) -> f32 {
+        let mut world = lone_cue_ball_world(true);
+        world.integrator = integrator;
+        world.shoot_q_ball(&CueStrike::default());
+        while !world.is_settled() {
+            world.step(dt);
+        }
+        world.balls[0].position.x - world.balls[0].initial_position.x
+    }
+
+    #[test]
+    fn rk4_stops_at_the_analytic_distance_even_with_large_steps() {
+        let solution = crate::analytic::SlideToRollSolution::new(PARAMS.cue_ball_initial_velocity, 0.0, true, &PARAMS);
+        let expected = solution.displacement_at(solution.transition_time() + solution.rolling_time());
+        assert!((stopping_position(Integrator::Rk4, 1.0 / 15.0) - expected).abs() < 0.005);
+    }
+
+    #[test]
+    fn euler_integrators_deviate_from_rk4_on_either_side() {
+        let dt = 1.0 / 15.0;
+        let rk4 = stopping_position(Integrator::Rk4, dt);
+        assert!(stopping_position(Integrator::ExplicitEuler, dt) > rk4);
+        assert!(stopping_position(Integrator::SemiImplicitEuler, dt) < rk4);
+    }
+
+    #[test]
+    fn phases_follow_the_shot_cycle() {
+        use GamePhase::*;
+        use PhaseEvent::*;
+        assert_eq!(Initial.next(SpacePressed), Some(Aiming));
+        assert_eq!(Aiming.next(SpacePressed), Some(Running));
+        assert_eq!(Running.next(BallsStopped), Some(Settled));
+        assert_eq!(Settled.next(SpacePressed), Some(Aiming));
+        assert_eq!(Running.next(SpacePressed), Some(Aiming));
+        // Nothing is moving outside a run
+        assert_eq!(Aiming.next(BallsStopped), None);
+        assert_eq!(Settled.next(BallsStopped), None);
+    }
+
+    #[test]
+    fn each_world_settles_on_its_own() {
+        let strike = CueStrike::default();
+        let mut short = SidePoolWorld::headless(0.9, true);
+        let mut long = lone_cue_ball_world(true);
+        for world in [&mut short, &mut long] {
+            world.handle(PhaseEvent::SpacePressed, &strike);
+            world.handle(PhaseEvent::SpacePressed, &strike);
+            assert_eq!(world.game_phase, GamePhase::Running);
+        }
+
+        let mut elapsed = 0.0;
+        while short.game_phase == GamePhase::Running && elapsed < 60.0 {
+            short.update(DT);
+            long.update(DT);
+            elapsed += DT;
+        }
+        assert_eq!(short.game_phase, GamePhase::Settled);
+        assert!(short.is_settled());
+        assert_eq!(long.game_phase, GamePhase::Running);
+
+        // Space from Settled puts the balls back ready for the next shot
+        short.handle(PhaseEvent::SpacePressed, &strike);
+        assert_eq!(short.game_phase, GamePhase::Aiming);
+        assert!(short.balls.iter().all(|b| b.active && b.position == b.initial_position));
+    }
+
+    #[test]
+    fn elevated_cue_makes_the_ball_hop() {
+        let mut world = lone_cue_ball_world(true);
+        let resting_y = TABLE_Y_POS - PARAMS.ball_radius;
+        world.shoot_q_ball(&CueStrike { elevation: 45.0, force_factor: 5.0, ..CueStrike::default() });
+        assert!(world.balls[0].airborne);
+
+        let mut highest = resting_y;
+        for _ in 0..180 {
+            world.step(DT);
+            highest = highest.min(world.balls[0].position.y);
+        }
+        assert!(resting_y - highest > 0.05);
+        // Bounces die away and it carries on along the cloth
+        assert!(!world.balls[0].airborne);
+        assert_eq!(world.balls[0].position.y, resting_y);
+        assert!(world.balls[0].linear_velocity.x > 0.0);
+    }
+
+    #[test]
+    fn bounce_height_falls_by_restitution_squared() {
+        let resting_y = TABLE_Y_POS - PARAMS.ball_radius;
+        let drop_height = 0.1;
+        let mut ball = ball_at(0.15, 0.0, 0.0);
+        ball.position.y = resting_y - drop_height;
+        ball.airborne = true;
+        let mut world = SidePoolWorld::with_balls(0.3, vec![ball]);
+
+        // Down to the slate, then up to the top of the first bounce
+        while world.balls[0].linear_velocity.y >= 0.0 {
+            world.step(1.0 / 1000.0);
+        }
+        while world.balls[0].linear_velocity.y < 0.0 {
+            world.step(1.0 / 1000.0);
+        }
+        let bounce_height = resting_y - world.balls[0].position.y;
+        let restitution = PARAMS.slate_restitution_coeff;
+        assert!((bounce_height - drop_height * restitution * restitution).abs() < 0.001);
+    }
+
+    #[test]
+    fn hopping_ball_passes_over_a_low_neighbour_only_when_clear() {
+        let mut high = ball_at(0.3, 0.15, 0.0);
+        high.position.y -= PARAMS.ball_radius * 2.5;
+        let low = ball_at(0.3 + PARAMS.ball_radius * 1.5, 0.0, 0.0);
+        assert!(time_since_contact(&high, &low, &PARAMS).is_none());
+
+        let mut grazing = ball_at(0.3, 0.15, 0.0);
+        grazing.position.y -= PARAMS.ball_radius;
+        assert!(time_since_contact(&grazing, &low, &PARAMS).is_some());
+    }
+}
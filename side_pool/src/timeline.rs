@@ -0,0 +1,175 @@
+use crate::side_pool::Ball;
+
+// Slow motion choices, as a fraction of real time
+const SPEEDS: [f32; 4] = [1.0, 0.5, 0.25, 0.1];
+
+// Every ball of one world at one physics step
+pub struct Snapshot {
+    pub time: f32,
+    pub balls: Vec<Ball>,
+}
+
+// A world's run so far, one snapshot per step, so it can be shown again at any time
+#[derive(Default)]
+pub struct History {
+    snapshots: Vec<Snapshot>,
+}
+
+impl History {
+    pub fn record(&mut self, time: f32, balls: &[Ball]) {
+        self.snapshots.push(Snapshot { time, balls: balls.to_vec() });
+    }
+
+    // The latest snapshot at or before `time`. Worlds that settled early just keep their last one.
+    pub fn at(&self, time: f32) -> Option<&Snapshot> {
+        let after = self.snapshots.partition_point(|s| s.time <= time + 1e-6);
+        self.snapshots.get(after.saturating_sub(1))
+    }
+}
+
+// Pause, slow motion and scrubbing, shared by every world so they stay in sync.
+// Scrubbing only changes what is shown: playing on from a scrubbed time replays the
+// history until it catches up with the live physics.
+pub struct Timeline {
+    pub paused: bool,
+    pub scrub_time: Option<f32>, // Shown instead of the live state
+    speed_index: usize,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            paused: false,
+            scrub_time: None,
+            speed_index: 0,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed_index]
+    }
+
+    pub fn cycle_speed(&mut self) {
+        self.speed_index = (self.speed_index + 1) % SPEEDS.len();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // Back to the live physics, as when a new shot starts
+    pub fn go_live(&mut self) {
+        self.paused = false;
+        self.scrub_time = None;
+    }
+
+    pub fn view_time(&self, live_time: f32) -> f32 {
+        self.scrub_time.unwrap_or(live_time)
+    }
+
+    // Jumps to a fraction of the way through the run and holds there
+    pub fn scrub_to(&mut self, fraction: f32, live_time: f32) {
+        self.paused = true;
+        self.scrub_time = Some(fraction.clamp(0.0, 1.0) * live_time);
+    }
+
+    pub fn step_back(&mut self, dt: f32, live_time: f32) {
+        self.paused = true;
+        self.scrub_time = Some((self.view_time(live_time) - dt).max(0.0));
+    }
+
+    // Forward one step through the history. True when already live, so the caller should step the physics instead.
+    pub fn step_forward(&mut self, dt: f32, live_time: f32) -> bool {
+        self.paused = true;
+        match self.scrub_time {
+            Some(time) => {
+                self.scrub_time = Some(time + dt).filter(|t| *t < live_time - 1e-6);
+                false
+            }
+            None => true,
+        }
+    }
+
+    // Physics time to run this frame: none while paused or replaying the history
+    pub fn advance(&mut self, frame_dt: f32, live_time: f32) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let dt = frame_dt * self.speed();
+        match self.scrub_time {
+            Some(time) => {
+                self.scrub_time = Some(time + dt).filter(|t| *t < live_time);
+                0.0
+            }
+            None => dt,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cue::CueStrike;
+    use crate::side_pool::SidePoolWorld;
+
+    const DT: f32 = 1.0 / 120.0;
+
+    #[test]
+    fn history_shows_each_world_as_it_was() {
+        let mut world = SidePoolWorld::headless(0.9, true);
+        world.shoot_q_ball(&CueStrike::default());
+        let start_x = world.balls[0].position.x;
+        for _ in 0..120 {
+            world.step(DT);
+        }
+
+        let start = world.history.at(0.0).unwrap();
+        assert_eq!(start.balls[0].position.x, start_x);
+        let halfway = world.history.at(0.5).unwrap();
+        assert!((halfway.time - 0.5).abs() < DT);
+        assert!(halfway.balls[0].position.x > start_x);
+        assert!(halfway.balls[0].position.x < world.balls[0].position.x);
+        // After the end it stays on the last step, like a world that has settled
+        assert_eq!(world.history.at(10.0).unwrap().balls[0].position.x, world.balls[0].position.x);
+    }
+
+    #[test]
+    fn slow_motion_runs_less_physics_per_frame() {
+        let mut timeline = Timeline::new();
+        assert_eq!(timeline.advance(0.1, 1.0), 0.1);
+        timeline.cycle_speed();
+        assert_eq!(timeline.advance(0.1, 1.0), 0.05);
+        timeline.toggle_pause();
+        assert_eq!(timeline.advance(0.1, 1.0), 0.0);
+    }
+
+    #[test]
+    fn stepping_back_and_forward_moves_through_the_history() {
+        let mut timeline = Timeline::new();
+        let live_time = 1.0;
+        timeline.step_back(DT, live_time);
+        timeline.step_back(DT, live_time);
+        assert!(timeline.paused);
+        assert!((timeline.view_time(live_time) - (1.0 - 2.0 * DT)).abs() < 1e-6);
+
+        assert!(!timeline.step_forward(DT, live_time));
+        assert!(!timeline.step_forward(DT, live_time));
+        // Caught up, the next step is real physics
+        assert_eq!(timeline.scrub_time, None);
+        assert!(timeline.step_forward(DT, live_time));
+    }
+
+    #[test]
+    fn playing_from_a_scrubbed_time_replays_then_goes_live() {
+        let mut timeline = Timeline::new();
+        timeline.scrub_to(0.5, 2.0);
+        assert_eq!(timeline.view_time(2.0), 1.0);
+        timeline.toggle_pause();
+
+        assert_eq!(timeline.advance(0.6, 2.0), 0.0);
+        assert!((timeline.view_time(2.0) - 1.6).abs() < 1e-6);
+        timeline.advance(0.6, 2.0);
+        assert_eq!(timeline.scrub_time, None);
+        assert_eq!(timeline.advance(0.1, 2.0), 0.1);
+    }
+}
@@ -0,0 +1,127 @@
+use serde::Serialize;
+
+use crate::side_pool::Ball;
+
+// State of one ball at one physics step
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TrajectorySample {
+    pub time: f32,
+    pub ball: usize, // Index into the world's balls, the cue ball is usually 0
+    pub position_x: f32,
+    pub position_y: f32,
+    pub linear_velocity_x: f32,
+    pub linear_velocity_y: f32,
+    pub angular_velocity: f32,
+    pub angle: f32,
+    pub is_slipping: bool,
+    pub active: bool,
+}
+
+// Every ball at every step of a single run, in long format (one row per ball per step)
+// so it loads straight into plotting tools
+#[derive(Default, Serialize)]
+pub struct Trajectory {
+    pub samples: Vec<TrajectorySample>,
+}
+
+impl Trajectory {
+    pub fn record(&mut self, time: f32, balls: &[Ball]) {
+        self.samples.extend(balls.iter().enumerate().map(|(i, b)| TrajectorySample {
+            time,
+            ball: i,
+            position_x: b.position.x,
+            position_y: b.position.y,
+            linear_velocity_x: b.linear_velocity.x,
+            linear_velocity_y: b.linear_velocity.y,
+            angular_velocity: b.angular_velocity,
+            angle: b.angle,
+            is_slipping: b.is_slipping,
+            active: b.active,
+        }));
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "time,ball,position_x,position_y,linear_velocity_x,linear_velocity_y,angular_velocity,angle,is_slipping,active\n",
+        );
+        for s in &self.samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                s.time,
+                s.ball,
+                s.position_x,
+                s.position_y,
+                s.linear_velocity_x,
+                s.linear_velocity_y,
+                s.angular_velocity,
+                s.angle,
+                s.is_slipping,
+                s.active
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("trajectory samples are plain numbers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cue::CueStrike;
+    use crate::side_pool::*;
+
+    // Samples of a single ball, in time order
+    fn ball(trajectory: &Trajectory, index: usize) -> impl Iterator<Item = &TrajectorySample> {
+        trajectory.samples.iter().filter(move |s| s.ball == index)
+    }
+
+    fn shot(steps: usize) -> SidePoolWorld {
+        let mut world = SidePoolWorld::headless(0.9, true);
+        world.shoot_q_ball(&CueStrike { tip_offset: 0.3, ..CueStrike::default() });
+        for _ in 0..steps {
+            world.step(1.0 / 60.0);
+        }
+        world
+    }
+
+    #[test]
+    fn every_ball_is_recorded_at_every_step() {
+        let world = shot(30);
+        let trajectory = &world.trajectory;
+        // The starting positions plus one row per step, for both balls
+        assert_eq!(trajectory.samples.len(), 31 * 2);
+        assert_eq!(ball(trajectory, 0).count(), 31);
+
+        let last = ball(trajectory, 0).last().unwrap();
+        assert_eq!(last.position_x, world.balls[0].position.x);
+        assert_eq!(last.angular_velocity, world.balls[0].angular_velocity);
+        assert!(ball(trajectory, 0).any(|s| s.is_slipping));
+        assert!(ball(trajectory, 0).zip(ball(trajectory, 0).skip(1)).all(|(a, b)| a.time < b.time));
+    }
+
+    #[test]
+    fn a_new_shot_starts_a_new_trajectory() {
+        let mut world = shot(30);
+        world.reset();
+        assert!(world.trajectory.samples.is_empty());
+        world.shoot_q_ball(&CueStrike::default());
+        assert_eq!(world.trajectory.samples.len(), 2);
+    }
+
+    #[test]
+    fn exports_hold_every_sample() {
+        let world = shot(10);
+        let csv = world.trajectory.to_csv();
+        assert_eq!(csv.lines().count(), world.trajectory.samples.len() + 1);
+        assert!(csv.starts_with("time,ball,position_x,"));
+
+        let json: serde_json::Value = serde_json::from_str(&world.trajectory.to_json()).unwrap();
+        let samples = json["samples"].as_array().unwrap();
+        assert_eq!(samples.len(), world.trajectory.samples.len());
+        assert_eq!(samples[0]["ball"], 0);
+        assert!(samples[0]["linear_velocity_x"].as_f64().unwrap() > 0.0);
+    }
+}